@@ -1,13 +1,28 @@
-use arboard::Clipboard;
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
 use crate::{
-    arguments::ReisbaseActionsArguments,
+    arguments::{ReisbaseActionsArguments, ReisbaseActionsArgumentsList},
+    clipboard::{self, ClipboardSelection},
+    config::{self, ConfigScope, Configuration, Setting},
     controller::Controller,
-    extensions::{PeekOption, ResultFromPredicate},
-    failures::{CustomReisActionWarning, CustomReisIOFailure},
+    encryption::{self, Encryption},
+    entry::{KeyedEntry, ReisbaseEntry},
+    environment::{self, EnvironmentVariable, Shell},
+    error_handler::ErrorHandler,
+    exchange::{ConflictStrategy, ExchangeFormat, ImportChange, ImportChangeKind},
+    extensions::ResultFromPredicate,
+    failures::{CustomFailureOperation, CustomReisActionWarning, CustomReisIOFailure},
+    input,
+    layers::LayeredEntry,
+    namespace,
+    reisbase::Reisbase,
+    search::SearchQuery,
+    storage,
     success::CustomSuccessOperation,
+    suggestions,
+    template::{MissingKeyPolicy, Template},
+    time,
 };
 
 #[derive(Debug, EnumIter)]
@@ -33,77 +48,507 @@ pub enum ReisbaseAction {
     GetAll {
         arguments: Vec<ReisbaseActionsArguments>,
     },
+    Find {
+        pattern: String,
+        arguments: Vec<ReisbaseActionsArguments>,
+    },
     Clear {
         arguments: Vec<ReisbaseActionsArguments>,
     },
+    Ttl {
+        key: String,
+        arguments: Vec<ReisbaseActionsArguments>,
+    },
+    History {
+        key: String,
+        arguments: Vec<ReisbaseActionsArguments>,
+    },
+    Revert {
+        key: String,
+        version: Option<usize>,
+        arguments: Vec<ReisbaseActionsArguments>,
+    },
+    Undo {
+        arguments: Vec<ReisbaseActionsArguments>,
+    },
+    Namespaces {
+        arguments: Vec<ReisbaseActionsArguments>,
+    },
+    CreateNamespace {
+        namespace: String,
+        arguments: Vec<ReisbaseActionsArguments>,
+    },
+    RenameNamespace {
+        namespace: String,
+        new_namespace: String,
+        arguments: Vec<ReisbaseActionsArguments>,
+    },
+    DropNamespace {
+        namespace: String,
+        arguments: Vec<ReisbaseActionsArguments>,
+    },
+    RestoreBackup {
+        arguments: Vec<ReisbaseActionsArguments>,
+    },
+    Export {
+        format: ExchangeFormat,
+        arguments: Vec<ReisbaseActionsArguments>,
+    },
+    Import {
+        /// The file the entries are read from, or stdin for `-`.
+        source: String,
+        format: ExchangeFormat,
+        arguments: Vec<ReisbaseActionsArguments>,
+    },
+    Run {
+        /// The program to run, followed by its arguments.
+        command: Vec<String>,
+        arguments: Vec<ReisbaseActionsArguments>,
+    },
+    Env {
+        shell: Shell,
+        arguments: Vec<ReisbaseActionsArguments>,
+    },
+    Render {
+        /// The file the template is read from, or stdin for `-`.
+        template: String,
+        arguments: Vec<ReisbaseActionsArguments>,
+    },
+    Config {
+        arguments: Vec<ReisbaseActionsArguments>,
+    },
+    ConfigGet {
+        setting: Setting,
+        arguments: Vec<ReisbaseActionsArguments>,
+    },
+    ConfigSet {
+        setting: Setting,
+        value: String,
+        arguments: Vec<ReisbaseActionsArguments>,
+    },
+    Init {
+        arguments: Vec<ReisbaseActionsArguments>,
+    },
+    Which {
+        arguments: Vec<ReisbaseActionsArguments>,
+    },
+    Encrypt {
+        arguments: Vec<ReisbaseActionsArguments>,
+    },
+    Decrypt {
+        arguments: Vec<ReisbaseActionsArguments>,
+    },
+    Rekey {
+        arguments: Vec<ReisbaseActionsArguments>,
+    },
+    Repair {
+        arguments: Vec<ReisbaseActionsArguments>,
+    },
 }
 
 impl ReisbaseAction {
     pub fn execute(
         controller: &mut Controller,
-    ) -> Result<CustomSuccessOperation, CustomReisActionWarning> {
+    ) -> Result<CustomSuccessOperation, CustomFailureOperation> {
         match &controller.action {
             ReisbaseAction::Set {
                 key,
                 value: new_value,
-                arguments: _,
-            } => match controller.database.get(key) {
-                Some(ref old_value) => Err(CustomReisActionWarning::entry_already_exists(
-                    key, old_value, new_value,
-                )),
+                arguments,
+            } => match controller.database.get_entry(key) {
+                Some(old_entry) => Err(CustomReisActionWarning::entry_already_exists(
+                    key, old_entry, new_value, arguments,
+                )
+                .into()),
                 None => {
-                    controller.database.insert(key, new_value);
-                    Ok(CustomSuccessOperation::insert(key, new_value))
+                    let entry = build_entry(new_value, arguments, None);
+                    let shown = entry.masked();
+                    if let Some(namespace) = arguments.namespace() {
+                        controller.database.create_namespace(namespace);
+                    }
+                    controller.database.insert(key, entry);
+                    Ok(CustomSuccessOperation::insert(key, &shown.value))
                 }
             },
-            ReisbaseAction::Get { key, arguments } => controller
-                .database
-                .get(key)
-                .peek(|value| {
-                    if arguments.contains(&ReisbaseActionsArguments::Clipboard) {
-                        text_to_clipboard(value);
+            ReisbaseAction::Get { key, arguments } => {
+                let layered = controller.layered();
+                let mut answers = layered.get_entries(key);
+                let Some((layer, entry)) = answers.next() else {
+                    return Err(entry_doesnt_exists(controller, key, None));
+                };
+                if controller.options.copy
+                    || arguments.contains(&ReisbaseActionsArguments::Clipboard)
+                {
+                    let selection = controller.options.clipboard;
+                    let default_clear_after = entry
+                        .secret
+                        .then_some(controller.options.secret_clear_after);
+                    match arguments
+                        .clear_after()
+                        .or(controller.options.clear_after)
+                        .or(default_clear_after)
+                    {
+                        Some(0) | None => clipboard::copy(selection, &entry.value)?,
+                        Some(seconds) => {
+                            clipboard::copy_temporarily(selection, &entry.value, seconds)?
+                        }
                     }
-                })
-                .map(CustomSuccessOperation::Get)
-                .ok_or_else(|| CustomReisActionWarning::entry_doesnt_exists(key, None)),
+                }
+                let entry = if arguments.contains(&ReisbaseActionsArguments::Reveal) {
+                    entry.clone()
+                } else {
+                    entry.masked()
+                };
+                if arguments.contains(&ReisbaseActionsArguments::Explain) {
+                    return Ok(CustomSuccessOperation::Explain {
+                        key: key.clone(),
+                        entry,
+                        layer: layer.clone(),
+                        shadowed: answers.map(|(layer, _)| layer.clone()).collect(),
+                    });
+                }
+                Ok(CustomSuccessOperation::get(
+                    key,
+                    &entry,
+                    arguments.has_description_flag(),
+                ))
+            }
             ReisbaseAction::Put {
                 key,
                 value,
+                arguments,
+            } => {
+                let Some(previous) = controller.database.get_entry(key).cloned() else {
+                    return Err(entry_doesnt_exists(controller, key, Some(value)));
+                };
+                let entry = build_entry(value, arguments, Some(&previous));
+                let shown = entry.masked();
+                controller.database.insert(key, entry);
+                Ok(CustomSuccessOperation::put(
+                    key,
+                    &shown.value,
+                    &previous.masked().value,
+                ))
+            }
+            ReisbaseAction::Del { key, arguments: _ } => {
+                if !controller.database.exists(key) {
+                    return Err(entry_doesnt_exists(controller, key, None));
+                }
+                let old_value = controller
+                    .database
+                    .delete(key)
+                    .map(|entry| entry.masked().value)
+                    .unwrap_or_default();
+                Ok(CustomSuccessOperation::delete(key, &old_value))
+            }
+            ReisbaseAction::GetAll { arguments } => {
+                let scope = arguments.namespace_scope();
+                if arguments.contains(&ReisbaseActionsArguments::Merged) {
+                    let entries: Vec<LayeredEntry> = controller
+                        .layered()
+                        .get_all(&scope)
+                        .into_iter()
+                        .filter(|layered| {
+                            arguments
+                                .description()
+                                .is_none_or(|term| layered.keyed.entry.description_contains(term))
+                        })
+                        .map(|mut layered| {
+                            layered.keyed.entry = layered.keyed.entry.masked();
+                            layered
+                        })
+                        .collect();
+                    if entries.is_empty() {
+                        return Err(CustomReisActionWarning::EmptyDatabase.into());
+                    }
+                    return Ok(CustomSuccessOperation::GetMerged { entries });
+                }
+                let (entries, warning) = match arguments.description() {
+                    Some(term) => (
+                        controller.database.search_descriptions(term, &scope),
+                        CustomReisActionWarning::NoMatchingEntries,
+                    ),
+                    None => (
+                        controller.database.get_all(&scope),
+                        CustomReisActionWarning::EmptyDatabase,
+                    ),
+                };
+                if entries.is_empty() {
+                    return Err(warning.into());
+                }
+                let entries = entries
+                    .into_iter()
+                    .map(|keyed| KeyedEntry {
+                        entry: keyed.entry.masked(),
+                        key: keyed.key,
+                    })
+                    .collect();
+                Ok(CustomSuccessOperation::GetAll { entries })
+            }
+            ReisbaseAction::Find { pattern, arguments } => {
+                let query = SearchQuery::new(
+                    pattern,
+                    arguments.search_mode(),
+                    arguments.search_fields(),
+                    arguments.contains(&ReisbaseActionsArguments::IgnoreCase),
+                )
+                .map_err(|error| CustomReisIOFailure::invalid_search_pattern(pattern, error))?;
+
+                let scope = arguments.namespace_scope();
+                let matches = controller.database.find(&query, &scope);
+                if matches.is_empty() {
+                    Err(CustomReisActionWarning::NoMatchingEntries.into())
+                } else {
+                    Ok(CustomSuccessOperation::Find { matches })
+                }
+            }
+            ReisbaseAction::Clear { arguments } => {
+                let scope = arguments.namespace_scope();
+                if controller.database.is_empty(&scope) {
+                    return Err(CustomReisActionWarning::EmptyDatabase.into());
+                }
+                Result::from_predicate(
+                    arguments.contains(&ReisbaseActionsArguments::Force),
+                    || CustomSuccessOperation::clear(controller.database.clear(&scope)),
+                    || CustomReisActionWarning::clear_without_force(arguments).into(),
+                )
+            }
+            ReisbaseAction::Ttl { key, arguments: _ } => controller
+                .database
+                .get_entry(key)
+                .map(|entry| CustomSuccessOperation::ttl(key, entry.expires_at, time::now()))
+                .ok_or_else(|| entry_doesnt_exists(controller, key, None)),
+            ReisbaseAction::History { key, arguments: _ } => {
+                let versions = controller.database.history(key);
+                if versions.is_empty() {
+                    return Err(CustomReisActionWarning::no_history(key).into());
+                }
+                Ok(CustomSuccessOperation::history(
+                    key,
+                    controller.database.get_entry(key),
+                    &versions,
+                ))
+            }
+            ReisbaseAction::Revert {
+                key,
+                version,
                 arguments: _,
-            } => Result::from_predicate(
-                controller.database.exists(key),
-                || {
-                    controller.database.insert(key, value);
-                    CustomSuccessOperation::put(key, value)
-                },
-                || CustomReisActionWarning::entry_doesnt_exists(key, Some(value)),
-            ),
-            ReisbaseAction::Del { key, arguments: _ } => Result::from_predicate(
-                controller.database.exists(key),
-                || {
-                    controller.database.delete(key);
-                    CustomSuccessOperation::delete(key)
-                },
-                || CustomReisActionWarning::entry_doesnt_exists(key, None),
-            ),
-            ReisbaseAction::GetAll { arguments: _ } => controller
+            } => {
+                if controller.database.history(key).is_empty() {
+                    return Err(CustomReisActionWarning::no_history(key).into());
+                }
+                let version = version.unwrap_or(1);
+                controller
+                    .database
+                    .revert(key, version)
+                    .map(|entry| CustomSuccessOperation::revert(key, version, entry.masked()))
+                    .ok_or_else(|| {
+                        CustomReisActionWarning::history_version_doesnt_exists(key, version).into()
+                    })
+            }
+            ReisbaseAction::Undo { arguments: _ } => controller
                 .database
-                .get_all()
-                .map(CustomSuccessOperation::GetAll)
-                .ok_or(CustomReisActionWarning::EmptyDatabase),
-            ReisbaseAction::Clear { arguments } => {
-                if controller.database.is_empty() {
-                    return Err(CustomReisActionWarning::EmptyDatabase);
+                .undo()
+                .map(CustomSuccessOperation::undo)
+                .ok_or_else(|| CustomReisActionWarning::NothingToUndo.into()),
+            ReisbaseAction::Namespaces { arguments: _ } => {
+                let namespaces = controller.database.namespaces();
+                if namespaces.is_empty() {
+                    Err(CustomReisActionWarning::NoNamespaces.into())
+                } else {
+                    Ok(CustomSuccessOperation::Namespaces { namespaces })
+                }
+            }
+            ReisbaseAction::CreateNamespace {
+                namespace,
+                arguments: _,
+            } => {
+                if !controller.database.create_namespace(namespace) {
+                    return Err(CustomReisActionWarning::namespace_already_exists(namespace).into());
+                }
+                Ok(CustomSuccessOperation::CreateNamespace {
+                    namespace: namespace.clone(),
+                })
+            }
+            ReisbaseAction::RenameNamespace {
+                namespace,
+                new_namespace,
+                arguments: _,
+            } => {
+                if !controller.database.has_namespace(namespace) {
+                    return Err(CustomReisActionWarning::namespace_doesnt_exists(namespace).into());
+                }
+                if controller.database.has_namespace(new_namespace) {
+                    return Err(
+                        CustomReisActionWarning::namespace_already_exists(new_namespace).into(),
+                    );
+                }
+                let moved = controller
+                    .database
+                    .rename_namespace(namespace, new_namespace);
+                Ok(CustomSuccessOperation::RenameNamespace {
+                    namespace: namespace.clone(),
+                    new_namespace: new_namespace.clone(),
+                    moved,
+                })
+            }
+            ReisbaseAction::DropNamespace {
+                namespace,
+                arguments,
+            } => {
+                if !controller.database.has_namespace(namespace) {
+                    return Err(CustomReisActionWarning::namespace_doesnt_exists(namespace).into());
                 }
                 Result::from_predicate(
                     arguments.contains(&ReisbaseActionsArguments::Force),
+                    || CustomSuccessOperation::DropNamespace {
+                        namespace: namespace.clone(),
+                        deleted: controller.database.drop_namespace(namespace),
+                    },
                     || {
-                        controller.database.clear();
-                        CustomSuccessOperation::clear()
+                        CustomReisActionWarning::drop_namespace_without_force(namespace, arguments)
+                            .into()
                     },
-                    CustomReisActionWarning::clear_without_force,
                 )
             }
+            ReisbaseAction::RestoreBackup { arguments: _ } => controller
+                .database
+                .restore_backup()
+                .map(|backup_path| CustomSuccessOperation::restore_backup(&backup_path))
+                .map_err(CustomFailureOperation::Error),
+            ReisbaseAction::Export { format, arguments } => {
+                let scope = arguments.namespace_scope();
+                let entries = controller.database.get_all(&scope);
+                if entries.is_empty() {
+                    return Err(CustomReisActionWarning::EmptyDatabase.into());
+                }
+                if !arguments.contains(&ReisbaseActionsArguments::Reveal) {
+                    refuse_secrets(&entries)?;
+                }
+                Ok(CustomSuccessOperation::Export {
+                    format: *format,
+                    count: entries.len(),
+                    contents: format.export(&entries)?,
+                })
+            }
+            ReisbaseAction::Import {
+                source,
+                format,
+                arguments,
+            } => import(&mut controller.database, source, *format, arguments),
+            ReisbaseAction::Run { command, arguments } => {
+                let variables = environment_variables(controller, arguments, true)?;
+                let exit_code = environment::run(command, &variables)?;
+                Ok(CustomSuccessOperation::Run {
+                    command: command.clone(),
+                    exit_code,
+                })
+            }
+            ReisbaseAction::Env { shell, arguments } => {
+                let revealed = arguments.contains(&ReisbaseActionsArguments::Reveal);
+                let variables = environment_variables(controller, arguments, revealed)?;
+                Ok(CustomSuccessOperation::Env {
+                    shell: *shell,
+                    variables,
+                })
+            }
+            ReisbaseAction::Render {
+                template,
+                arguments,
+            } => render(controller, template, arguments),
+            ReisbaseAction::Config { arguments: _ } => {
+                let settings = Configuration::load()?.settings();
+                if settings.is_empty() {
+                    Err(CustomReisActionWarning::NoSettings.into())
+                } else {
+                    Ok(CustomSuccessOperation::Config { settings })
+                }
+            }
+            ReisbaseAction::ConfigGet {
+                setting,
+                arguments: _,
+            } => Configuration::load()?
+                .get(setting)
+                .map(|setting| CustomSuccessOperation::ConfigGet { setting })
+                .ok_or_else(|| {
+                    CustomReisActionWarning::SettingNotSet {
+                        setting: setting.name(),
+                    }
+                    .into()
+                }),
+            ReisbaseAction::ConfigSet {
+                setting,
+                value,
+                arguments,
+            } => {
+                let scope = if arguments.contains(&ReisbaseActionsArguments::Project) {
+                    ConfigScope::Project
+                } else {
+                    ConfigScope::User
+                };
+                let path = config::set(setting, value, scope)?;
+                Ok(CustomSuccessOperation::ConfigSet {
+                    name: setting.name(),
+                    value: value.clone(),
+                    path,
+                    scope,
+                })
+            }
+            ReisbaseAction::Init { arguments: _ } => {
+                let location = &controller.location;
+                let created = storage::create_database(&location.path)
+                    .map_err(ErrorHandler::handle_io_error)?;
+                if !created {
+                    return Err(CustomReisActionWarning::DatabaseAlreadyExists {
+                        path: location.path.clone(),
+                    }
+                    .into());
+                }
+                Ok(CustomSuccessOperation::Init {
+                    path: location.path.clone(),
+                    source: location.source,
+                })
+            }
+            ReisbaseAction::Which { arguments: _ } => Ok(CustomSuccessOperation::Which {
+                path: controller.location.path.clone(),
+                source: controller.location.source,
+            }),
+            ReisbaseAction::Encrypt { arguments: _ } => {
+                if controller.database.is_encrypted() {
+                    return Err(CustomReisActionWarning::AlreadyEncrypted.into());
+                }
+                let path = controller.database.path().to_owned();
+                let passphrase = encryption::first_passphrase(&path)?;
+                controller
+                    .database
+                    .set_encryption(Some(Encryption::new(&passphrase)?));
+                Ok(CustomSuccessOperation::Encrypt { path })
+            }
+            ReisbaseAction::Decrypt { arguments: _ } => {
+                if !controller.database.is_encrypted() {
+                    return Err(CustomReisActionWarning::NotEncrypted.into());
+                }
+                controller.database.set_encryption(None);
+                Ok(CustomSuccessOperation::Decrypt {
+                    path: controller.database.path().to_owned(),
+                })
+            }
+            ReisbaseAction::Rekey { arguments: _ } => {
+                if !controller.database.is_encrypted() {
+                    return Err(CustomReisActionWarning::NotEncrypted.into());
+                }
+                let path = controller.database.path().to_owned();
+                let passphrase = encryption::new_passphrase(&path)?;
+                controller
+                    .database
+                    .set_encryption(Some(Encryption::new(&passphrase)?));
+                Ok(CustomSuccessOperation::Rekey { path })
+            }
+            ReisbaseAction::Repair { arguments: _ } => controller
+                .database
+                .repair()
+                .map(CustomSuccessOperation::repair)
+                .map_err(CustomFailureOperation::Error),
         }
     }
 
@@ -112,13 +557,20 @@ impl ReisbaseAction {
         key: Option<String>,
         value: Option<String>,
         arguments: Vec<ReisbaseActionsArguments>,
+        clipboard: ClipboardSelection,
     ) -> Result<ReisbaseAction, CustomReisIOFailure> {
-        let reisbase_action = ReisbaseAction::first(action)
-            .ok_or_else(|| CustomReisIOFailure::unknown_action_requested(action))?;
+        let reisbase_action = ReisbaseAction::first(action).ok_or_else(|| {
+            CustomReisIOFailure::unknown_action_requested(
+                action,
+                &ReisbaseAction::suggestions(action),
+            )
+        })?;
         let action_name = reisbase_action.action_name();
         let action = match reisbase_action {
             ReisbaseAction::Set { .. } => {
-                let (key, value) = parse_key_and_value(key, value, action_name)?;
+                let value =
+                    input::read_value(action_name, key.as_deref(), value, &arguments, clipboard)?;
+                let (key, value) = parse_key_and_value(key, value, action_name, &arguments)?;
                 ReisbaseAction::Set {
                     key,
                     value,
@@ -126,11 +578,13 @@ impl ReisbaseAction {
                 }
             }
             ReisbaseAction::Get { .. } => {
-                let key = parse_key_or_value(key, action_name)?;
+                let key = parse_key(key, action_name, &arguments)?;
                 ReisbaseAction::Get { key, arguments }
             }
             ReisbaseAction::Put { .. } => {
-                let (key, value) = parse_key_and_value(key, value, action_name)?;
+                let value =
+                    input::read_value(action_name, key.as_deref(), value, &arguments, clipboard)?;
+                let (key, value) = parse_key_and_value(key, value, action_name, &arguments)?;
                 ReisbaseAction::Put {
                     key,
                     value,
@@ -138,11 +592,114 @@ impl ReisbaseAction {
                 }
             }
             ReisbaseAction::Del { .. } => {
-                let key = parse_key_or_value(key, action_name)?;
+                let key = parse_key(key, action_name, &arguments)?;
                 ReisbaseAction::Del { key, arguments }
             }
             ReisbaseAction::GetAll { .. } => ReisbaseAction::GetAll { arguments },
+            ReisbaseAction::Find { .. } => {
+                let pattern = parse_key_or_value(key, action_name)?;
+                ReisbaseAction::Find { pattern, arguments }
+            }
             ReisbaseAction::Clear { .. } => ReisbaseAction::Clear { arguments },
+            ReisbaseAction::Ttl { .. } => {
+                let key = parse_key(key, action_name, &arguments)?;
+                ReisbaseAction::Ttl { key, arguments }
+            }
+            ReisbaseAction::History { .. } => {
+                let key = parse_key(key, action_name, &arguments)?;
+                ReisbaseAction::History { key, arguments }
+            }
+            ReisbaseAction::Revert { .. } => {
+                let key = parse_key(key, action_name, &arguments)?;
+                let version = value.map(|value| parse_version(&value)).transpose()?;
+                ReisbaseAction::Revert {
+                    key,
+                    version,
+                    arguments,
+                }
+            }
+            ReisbaseAction::Undo { .. } => ReisbaseAction::Undo { arguments },
+            ReisbaseAction::Namespaces { .. } => ReisbaseAction::Namespaces { arguments },
+            ReisbaseAction::CreateNamespace { .. } => {
+                let namespace = parse_namespace(key, action_name)?;
+                ReisbaseAction::CreateNamespace {
+                    namespace,
+                    arguments,
+                }
+            }
+            ReisbaseAction::RenameNamespace { .. } => {
+                let namespace = parse_namespace(key, action_name)?;
+                let new_namespace = parse_namespace(value, action_name)?;
+                ReisbaseAction::RenameNamespace {
+                    namespace,
+                    new_namespace,
+                    arguments,
+                }
+            }
+            ReisbaseAction::DropNamespace { .. } => {
+                let namespace = parse_namespace(key, action_name)?;
+                ReisbaseAction::DropNamespace {
+                    namespace,
+                    arguments,
+                }
+            }
+            ReisbaseAction::RestoreBackup { .. } => ReisbaseAction::RestoreBackup { arguments },
+            ReisbaseAction::Export { .. } => ReisbaseAction::Export {
+                format: arguments.exchange_format().unwrap_or_default(),
+                arguments,
+            },
+            ReisbaseAction::Import { .. } => {
+                let source = parse_key_or_value(key, action_name)?;
+                let format = arguments
+                    .exchange_format()
+                    .or_else(|| ExchangeFormat::from_path(&source))
+                    .ok_or_else(|| CustomReisIOFailure::missing_argument_value("--format"))?;
+                ReisbaseAction::Import {
+                    source,
+                    format,
+                    arguments,
+                }
+            }
+            ReisbaseAction::Run { .. } => ReisbaseAction::Run {
+                command: arguments
+                    .command()
+                    .filter(|command| !command.is_empty())
+                    .ok_or_else(CustomReisIOFailure::missing_command)?
+                    .to_vec(),
+                arguments,
+            },
+            ReisbaseAction::Env { .. } => ReisbaseAction::Env {
+                shell: arguments.shell(),
+                arguments,
+            },
+            ReisbaseAction::Render { .. } => {
+                let template = parse_key_or_value(key, action_name)?;
+                ReisbaseAction::Render {
+                    template,
+                    arguments,
+                }
+            }
+            ReisbaseAction::Config { .. } => ReisbaseAction::Config { arguments },
+            ReisbaseAction::ConfigGet { .. } => {
+                let setting = parse_setting(key, action_name)?;
+                ReisbaseAction::ConfigGet { setting, arguments }
+            }
+            ReisbaseAction::ConfigSet { .. } => {
+                let setting = parse_setting(key, action_name)?;
+                let value = parse_key_or_value(value, action_name)?;
+                setting.parse(&value)?;
+                ReisbaseAction::ConfigSet {
+                    setting,
+                    value,
+                    arguments,
+                }
+            }
+            ReisbaseAction::Init { .. } => ReisbaseAction::Init { arguments },
+            ReisbaseAction::Which { .. } => ReisbaseAction::Which { arguments },
+            ReisbaseAction::Encrypt { .. } => ReisbaseAction::Encrypt { arguments },
+            ReisbaseAction::Decrypt { .. } => ReisbaseAction::Decrypt { arguments },
+            ReisbaseAction::Rekey { .. } => ReisbaseAction::Rekey { arguments },
+            ReisbaseAction::Repair { .. } => ReisbaseAction::Repair { arguments },
         };
 
         Ok(action)
@@ -155,7 +712,31 @@ impl ReisbaseAction {
             ReisbaseAction::Put { .. } => "Put",
             ReisbaseAction::Del { .. } => "Delete",
             ReisbaseAction::GetAll { .. } => "Get All",
+            ReisbaseAction::Find { .. } => "Find",
             ReisbaseAction::Clear { .. } => "Clear",
+            ReisbaseAction::Ttl { .. } => "Time to Live",
+            ReisbaseAction::History { .. } => "History",
+            ReisbaseAction::Revert { .. } => "Revert",
+            ReisbaseAction::Undo { .. } => "Undo",
+            ReisbaseAction::Namespaces { .. } => "Namespaces",
+            ReisbaseAction::CreateNamespace { .. } => "Create Namespace",
+            ReisbaseAction::RenameNamespace { .. } => "Rename Namespace",
+            ReisbaseAction::DropNamespace { .. } => "Drop Namespace",
+            ReisbaseAction::RestoreBackup { .. } => "Restore Backup",
+            ReisbaseAction::Export { .. } => "Export",
+            ReisbaseAction::Import { .. } => "Import",
+            ReisbaseAction::Run { .. } => "Run",
+            ReisbaseAction::Env { .. } => "Env",
+            ReisbaseAction::Render { .. } => "Render",
+            ReisbaseAction::Config { .. } => "Config",
+            ReisbaseAction::ConfigGet { .. } => "Config Get",
+            ReisbaseAction::ConfigSet { .. } => "Config Set",
+            ReisbaseAction::Init { .. } => "Init",
+            ReisbaseAction::Which { .. } => "Which",
+            ReisbaseAction::Encrypt { .. } => "Encrypt",
+            ReisbaseAction::Decrypt { .. } => "Decrypt",
+            ReisbaseAction::Rekey { .. } => "Rekey",
+            ReisbaseAction::Repair { .. } => "Repair",
         }
     }
 
@@ -163,7 +744,63 @@ impl ReisbaseAction {
         ReisbaseAction::iter().find(|ac| ac.has_same_name(action))
     }
 
-    pub fn names(&self) -> &[&str] {
+    /// Returns the action names that are likely typos of `action`, from the closest.
+    pub fn suggestions(action: &str) -> Vec<&'static str> {
+        suggestions::closest(action, ReisbaseAction::long_names())
+    }
+
+    /// Every action name except the single letter shortcuts, which are too close to one
+    /// another to be told apart as typos.
+    fn long_names() -> Vec<&'static str> {
+        ReisbaseAction::iter()
+            .flat_map(|action| action.names())
+            .copied()
+            .filter(|name| name.len() > 1)
+            .collect()
+    }
+
+    /// The name this action is written with when it is requested again, such as when
+    /// retrying it.
+    pub fn full_name(&self) -> &'static str {
+        self.names().last().copied().unwrap_or_default()
+    }
+
+    pub fn arguments(&self) -> &[ReisbaseActionsArguments] {
+        match self {
+            ReisbaseAction::Set { arguments, .. }
+            | ReisbaseAction::Get { arguments, .. }
+            | ReisbaseAction::Put { arguments, .. }
+            | ReisbaseAction::Del { arguments, .. }
+            | ReisbaseAction::GetAll { arguments }
+            | ReisbaseAction::Find { arguments, .. }
+            | ReisbaseAction::Clear { arguments }
+            | ReisbaseAction::Ttl { arguments, .. }
+            | ReisbaseAction::History { arguments, .. }
+            | ReisbaseAction::Revert { arguments, .. }
+            | ReisbaseAction::Undo { arguments }
+            | ReisbaseAction::Namespaces { arguments }
+            | ReisbaseAction::CreateNamespace { arguments, .. }
+            | ReisbaseAction::RenameNamespace { arguments, .. }
+            | ReisbaseAction::DropNamespace { arguments, .. }
+            | ReisbaseAction::RestoreBackup { arguments }
+            | ReisbaseAction::Export { arguments, .. }
+            | ReisbaseAction::Import { arguments, .. }
+            | ReisbaseAction::Run { arguments, .. }
+            | ReisbaseAction::Env { arguments, .. }
+            | ReisbaseAction::Render { arguments, .. }
+            | ReisbaseAction::Config { arguments }
+            | ReisbaseAction::ConfigGet { arguments, .. }
+            | ReisbaseAction::ConfigSet { arguments, .. }
+            | ReisbaseAction::Init { arguments }
+            | ReisbaseAction::Which { arguments }
+            | ReisbaseAction::Encrypt { arguments }
+            | ReisbaseAction::Decrypt { arguments }
+            | ReisbaseAction::Rekey { arguments }
+            | ReisbaseAction::Repair { arguments } => arguments,
+        }
+    }
+
+    pub fn names(&self) -> &'static [&'static str] {
         match self {
             ReisbaseAction::Set {
                 key: _,
@@ -184,10 +821,118 @@ impl ReisbaseAction {
                 arguments: _,
             } => &["d", "del"],
             ReisbaseAction::GetAll { arguments: _ } => &["ga", "getall"],
+            ReisbaseAction::Find {
+                pattern: _,
+                arguments: _,
+            } => &["f", "find"],
             ReisbaseAction::Clear { arguments: _ } => &["c", "clr"],
+            ReisbaseAction::Ttl {
+                key: _,
+                arguments: _,
+            } => &["t", "ttl"],
+            ReisbaseAction::History {
+                key: _,
+                arguments: _,
+            } => &["h", "history"],
+            ReisbaseAction::Revert {
+                key: _,
+                version: _,
+                arguments: _,
+            } => &["rv", "revert"],
+            ReisbaseAction::Undo { arguments: _ } => &["u", "undo"],
+            ReisbaseAction::Namespaces { arguments: _ } => &["ns", "namespaces"],
+            ReisbaseAction::CreateNamespace {
+                namespace: _,
+                arguments: _,
+            } => &["nsc", "ns-create"],
+            ReisbaseAction::RenameNamespace {
+                namespace: _,
+                new_namespace: _,
+                arguments: _,
+            } => &["nsr", "ns-rename"],
+            ReisbaseAction::DropNamespace {
+                namespace: _,
+                arguments: _,
+            } => &["nsd", "ns-drop"],
+            ReisbaseAction::RestoreBackup { arguments: _ } => &["rb", "restore-backup"],
+            ReisbaseAction::Export {
+                format: _,
+                arguments: _,
+            } => &["ex", "export"],
+            ReisbaseAction::Import {
+                source: _,
+                format: _,
+                arguments: _,
+            } => &["im", "import"],
+            ReisbaseAction::Run {
+                command: _,
+                arguments: _,
+            } => &["run"],
+            ReisbaseAction::Env {
+                shell: _,
+                arguments: _,
+            } => &["env"],
+            ReisbaseAction::Render {
+                template: _,
+                arguments: _,
+            } => &["render"],
+            ReisbaseAction::Config { arguments: _ } => &["cfg", "config"],
+            ReisbaseAction::ConfigGet {
+                setting: _,
+                arguments: _,
+            } => &["cfgg", "config-get"],
+            ReisbaseAction::ConfigSet {
+                setting: _,
+                value: _,
+                arguments: _,
+            } => &["cfgs", "config-set"],
+            ReisbaseAction::Init { arguments: _ } => &["init"],
+            ReisbaseAction::Which { arguments: _ } => &["which"],
+            ReisbaseAction::Encrypt { arguments: _ } => &["encrypt"],
+            ReisbaseAction::Decrypt { arguments: _ } => &["decrypt"],
+            ReisbaseAction::Rekey { arguments: _ } => &["rekey"],
+            ReisbaseAction::Repair { arguments: _ } => &["rp", "repair"],
         }
     }
 
+    /// Whether this action can run on a database with corrupt lines.
+    pub fn tolerates_corruption(&self) -> bool {
+        matches!(self, ReisbaseAction::Repair { .. })
+    }
+
+    /// Whether this action reads through the databases below the one in use.
+    pub fn reads_layers(&self) -> bool {
+        match self {
+            ReisbaseAction::Get { .. }
+            | ReisbaseAction::Run { .. }
+            | ReisbaseAction::Env { .. }
+            | ReisbaseAction::Render { .. } => true,
+            ReisbaseAction::GetAll { arguments } => {
+                arguments.contains(&ReisbaseActionsArguments::Merged)
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether this action reads or writes the database, instead of only working with its
+    /// location.
+    pub fn needs_database(&self) -> bool {
+        !matches!(
+            self,
+            ReisbaseAction::Config { .. }
+                | ReisbaseAction::ConfigGet { .. }
+                | ReisbaseAction::ConfigSet { .. }
+                | ReisbaseAction::Init { .. }
+                | ReisbaseAction::Which { .. }
+        )
+    }
+
+    /// Whether this action creates a new database where the current directory is, instead of
+    /// looking for an existing one.
+    pub fn creates_database(&self) -> bool {
+        matches!(self, ReisbaseAction::Init { .. })
+    }
+
     pub fn has_same_name(&self, action: &str) -> bool {
         self.names().contains(&action)
     }
@@ -223,7 +968,31 @@ impl ReisbaseAction {
             ReisbaseAction::Put { .. } => true,
             ReisbaseAction::Del { .. } => true,
             ReisbaseAction::GetAll { .. } => false,
+            ReisbaseAction::Find { .. } => true,
             ReisbaseAction::Clear { .. } => false,
+            ReisbaseAction::Ttl { .. } => true,
+            ReisbaseAction::History { .. } => true,
+            ReisbaseAction::Revert { .. } => true,
+            ReisbaseAction::Undo { .. } => false,
+            ReisbaseAction::Namespaces { .. } => false,
+            ReisbaseAction::CreateNamespace { .. } => true,
+            ReisbaseAction::RenameNamespace { .. } => true,
+            ReisbaseAction::DropNamespace { .. } => true,
+            ReisbaseAction::RestoreBackup { .. } => false,
+            ReisbaseAction::Export { .. } => false,
+            ReisbaseAction::Import { .. } => true,
+            ReisbaseAction::Run { .. } => false,
+            ReisbaseAction::Env { .. } => false,
+            ReisbaseAction::Render { .. } => true,
+            ReisbaseAction::Config { .. } => false,
+            ReisbaseAction::ConfigGet { .. } => true,
+            ReisbaseAction::ConfigSet { .. } => true,
+            ReisbaseAction::Init { .. } => false,
+            ReisbaseAction::Which { .. } => false,
+            ReisbaseAction::Encrypt { .. } => false,
+            ReisbaseAction::Decrypt { .. } => false,
+            ReisbaseAction::Rekey { .. } => false,
+            ReisbaseAction::Repair { .. } => false,
         }
     }
     fn has_value(&self) -> bool {
@@ -233,27 +1002,311 @@ impl ReisbaseAction {
             ReisbaseAction::Put { .. } => true,
             ReisbaseAction::Del { .. } => false,
             ReisbaseAction::GetAll { .. } => false,
+            ReisbaseAction::Find { .. } => false,
             ReisbaseAction::Clear { .. } => false,
+            ReisbaseAction::Ttl { .. } => false,
+            ReisbaseAction::History { .. } => false,
+            ReisbaseAction::Revert { .. } => true,
+            ReisbaseAction::Undo { .. } => false,
+            ReisbaseAction::Namespaces { .. } => false,
+            ReisbaseAction::CreateNamespace { .. } => false,
+            ReisbaseAction::RenameNamespace { .. } => true,
+            ReisbaseAction::DropNamespace { .. } => false,
+            ReisbaseAction::RestoreBackup { .. } => false,
+            ReisbaseAction::Export { .. } => false,
+            ReisbaseAction::Import { .. } => false,
+            ReisbaseAction::Run { .. } => false,
+            ReisbaseAction::Env { .. } => false,
+            ReisbaseAction::Render { .. } => false,
+            ReisbaseAction::Config { .. } => false,
+            ReisbaseAction::ConfigGet { .. } => false,
+            ReisbaseAction::ConfigSet { .. } => true,
+            ReisbaseAction::Init { .. } => false,
+            ReisbaseAction::Which { .. } => false,
+            ReisbaseAction::Encrypt { .. } => false,
+            ReisbaseAction::Decrypt { .. } => false,
+            ReisbaseAction::Rekey { .. } => false,
+            ReisbaseAction::Repair { .. } => false,
         }
     }
 }
 
-fn text_to_clipboard(value: &str) {
-    if let Ok(mut clipboard) = Clipboard::new() {
-        _ = clipboard.set_text(value);
+/// Fails if any of `entries` is secret, since its value is only written out when revealed.
+fn refuse_secrets(entries: &[KeyedEntry]) -> Result<(), CustomReisActionWarning> {
+    let keys = entries
+        .iter()
+        .filter(|keyed| keyed.entry.secret)
+        .map(|keyed| keyed.key.clone())
+        .collect::<Vec<String>>();
+    if keys.is_empty() {
+        Ok(())
+    } else {
+        Err(CustomReisActionWarning::SecretsNotRevealed { keys })
+    }
+}
+
+/// The entries the run and env actions set as variables, read through every layer. Secret
+/// entries are refused unless `revealed` is set.
+fn environment_variables(
+    controller: &Controller,
+    arguments: &[ReisbaseActionsArguments],
+    revealed: bool,
+) -> Result<Vec<EnvironmentVariable>, CustomFailureOperation> {
+    let scope = arguments.namespace_scope();
+    let entries = controller
+        .layered()
+        .get_all(&scope)
+        .into_iter()
+        .map(|layered| layered.keyed)
+        .collect::<Vec<KeyedEntry>>();
+    if entries.is_empty() {
+        return Err(CustomReisActionWarning::EmptyDatabase.into());
     }
+    if !revealed {
+        refuse_secrets(&entries)?;
+    }
+    environment::variables(entries, &scope, arguments.prefix()).map_err(Into::into)
+}
+
+/// Renders the template read from `path` with the values of the entries, read through every
+/// layer. Templates using secret entries are refused unless they are revealed.
+fn render(
+    controller: &Controller,
+    path: &str,
+    arguments: &[ReisbaseActionsArguments],
+) -> Result<CustomSuccessOperation, CustomFailureOperation> {
+    let source = input::source_name(path);
+    let template = Template::parse(&input::read_contents(path)?)
+        .map_err(|error| CustomReisIOFailure::invalid_template(source, &error))?;
+    let layered = controller.layered();
+    let entry_of = |key: &str| {
+        layered
+            .get_entry(&namespace::qualify(key, arguments.namespace()))
+            .map(|(_, entry)| entry)
+    };
+    let value_of = |key: &str| entry_of(key).map(|entry| entry.value.clone());
+
+    let secrets = template
+        .keys()
+        .into_iter()
+        .filter(|key| entry_of(key).is_some_and(|entry| entry.secret))
+        .map(String::from)
+        .collect::<Vec<String>>();
+    if !secrets.is_empty() && !arguments.contains(&ReisbaseActionsArguments::Reveal) {
+        return Err(CustomReisActionWarning::SecretsNotRevealed { keys: secrets }.into());
+    }
+
+    let missing = template
+        .missing_keys(value_of)
+        .into_iter()
+        .map(String::from)
+        .collect::<Vec<String>>();
+    let policy = arguments.missing_key_policy();
+    if policy == MissingKeyPolicy::Fail && !missing.is_empty() {
+        return Err(CustomReisActionWarning::MissingTemplateKeys {
+            source: source.to_owned(),
+            keys: missing,
+        }
+        .into());
+    }
+    let default_value = match policy {
+        MissingKeyPolicy::Default => Some(arguments.default_value().unwrap_or_default()),
+        MissingKeyPolicy::Fail | MissingKeyPolicy::Leave => None,
+    };
+    Ok(CustomSuccessOperation::Render {
+        source: source.to_owned(),
+        contents: template.render(value_of, default_value),
+        missing,
+    })
+}
+
+/// Imports the entries read from `source` as described in [`ConflictStrategy`], or only
+/// reports what would change for a dry run.
+fn import(
+    database: &mut Reisbase,
+    source: &str,
+    format: ExchangeFormat,
+    arguments: &[ReisbaseActionsArguments],
+) -> Result<CustomSuccessOperation, CustomFailureOperation> {
+    let contents = input::read_contents(source)?;
+    let strategy = arguments.conflict_strategy();
+    let mut changes = Vec::new();
+    let mut writes = Vec::new();
+    let mut conflicts = Vec::new();
+
+    for keyed in format.import(&contents)? {
+        let key = namespace::qualify(&keyed.key, arguments.namespace());
+        let entry_arguments = imported_entry_arguments(&keyed.entry);
+        let previous = database.get_entry(&key);
+        let change = match (previous, strategy) {
+            (None, _) => ImportChangeKind::Added,
+            (Some(_), ConflictStrategy::Overwrite) => ImportChangeKind::Overwritten,
+            (Some(_), ConflictStrategy::Skip) => ImportChangeKind::Skipped,
+            (Some(previous), ConflictStrategy::Fail | ConflictStrategy::Prompt) => {
+                conflicts.push(CustomReisActionWarning::entry_already_exists(
+                    &key,
+                    previous,
+                    &keyed.entry.value,
+                    &entry_arguments,
+                ));
+                ImportChangeKind::Conflict
+            }
+        };
+        if matches!(
+            change,
+            ImportChangeKind::Added | ImportChangeKind::Overwritten
+        ) {
+            writes.push((
+                key.clone(),
+                build_entry(&keyed.entry.value, &entry_arguments, previous),
+            ));
+        }
+        changes.push(ImportChange {
+            key,
+            value: keyed.entry.masked().value,
+            change,
+        });
+    }
+
+    let source = input::source_name(source).to_owned();
+    if arguments.contains(&ReisbaseActionsArguments::DryRun) {
+        return Ok(CustomSuccessOperation::Import {
+            source,
+            dry_run: true,
+            changes,
+        });
+    }
+    if !conflicts.is_empty() && strategy == ConflictStrategy::Fail {
+        return Err(CustomReisActionWarning::ImportConflicts {
+            source,
+            imported: 0,
+            conflicts,
+            prompt: false,
+        }
+        .into());
+    }
+
+    let imported = writes.len();
+    if let Some(namespace) = arguments.namespace().filter(|_| imported > 0) {
+        database.create_namespace(namespace);
+    }
+    for (key, entry) in writes {
+        database.insert(&key, entry);
+    }
+    if conflicts.is_empty() {
+        Ok(CustomSuccessOperation::Import {
+            source,
+            dry_run: false,
+            changes,
+        })
+    } else {
+        Err(CustomReisActionWarning::ImportConflicts {
+            source,
+            imported,
+            conflicts,
+            prompt: true,
+        }
+        .into())
+    }
+}
+
+/// The arguments that give an entry written by Set or Put the metadata of `entry`, so an
+/// imported entry can be written, or retried, like one of theirs.
+fn imported_entry_arguments(entry: &ReisbaseEntry) -> Vec<ReisbaseActionsArguments> {
+    let mut arguments = Vec::new();
+    if let Some(description) = &entry.description {
+        arguments.push(ReisbaseActionsArguments::Description(Some(
+            description.clone(),
+        )));
+    }
+    if let Some(expires_at) = entry.expires_at {
+        arguments.push(ReisbaseActionsArguments::Expires(expires_at));
+    }
+    if entry.secret {
+        arguments.push(ReisbaseActionsArguments::Secret);
+    }
+    arguments
+}
+
+fn entry_doesnt_exists(
+    controller: &Controller,
+    key: &str,
+    value: Option<&str>,
+) -> CustomFailureOperation {
+    CustomReisActionWarning::entry_doesnt_exists(
+        &controller.action,
+        key,
+        value,
+        controller.database.similar_keys(key),
+    )
+    .into()
+}
+
+/// Builds the entry written by Set and Put. Metadata that isn't passed as an argument is kept
+/// from the entry being replaced, if any.
+fn build_entry(
+    value: &str,
+    arguments: &[ReisbaseActionsArguments],
+    previous: Option<&ReisbaseEntry>,
+) -> ReisbaseEntry {
+    let mut entry = ReisbaseEntry::new(value);
+    entry.description = arguments
+        .description()
+        .map(String::from)
+        .or_else(|| previous.and_then(|previous| previous.description.clone()));
+    entry.expires_at = arguments
+        .expiration(time::now())
+        .or_else(|| previous.and_then(|previous| previous.expires_at));
+    entry.secret = arguments.contains(&ReisbaseActionsArguments::Secret)
+        || previous.is_some_and(|previous| previous.secret);
+    entry
 }
 
 fn parse_key_or_value(s: Option<String>, action_name: &str) -> Result<String, CustomReisIOFailure> {
     s.ok_or_else(|| CustomReisIOFailure::invalid_action_arguments(action_name))
 }
 
+/// Parses the key of an action, placing it inside the namespace passed with `--ns`, if any.
+fn parse_key(
+    key: Option<String>,
+    action_name: &str,
+    arguments: &[ReisbaseActionsArguments],
+) -> Result<String, CustomReisIOFailure> {
+    let key = parse_key_or_value(key, action_name)?;
+    Ok(namespace::qualify(&key, arguments.namespace()))
+}
+
+fn parse_setting(
+    setting: Option<String>,
+    action_name: &str,
+) -> Result<Setting, CustomReisIOFailure> {
+    let setting = parse_key_or_value(setting, action_name)?;
+    Setting::new(&setting)
+}
+
+fn parse_namespace(
+    namespace: Option<String>,
+    action_name: &str,
+) -> Result<String, CustomReisIOFailure> {
+    let namespace = parse_key_or_value(namespace, action_name)?;
+    namespace::validate(&namespace).map(String::from)
+}
+
+fn parse_version(version: &str) -> Result<usize, CustomReisIOFailure> {
+    version
+        .parse::<usize>()
+        .ok()
+        .filter(|version| *version > 0)
+        .ok_or_else(|| CustomReisIOFailure::invalid_argument_value("version", version))
+}
+
 fn parse_key_and_value(
     key: Option<String>,
     value: Option<String>,
     action_name: &str,
+    arguments: &[ReisbaseActionsArguments],
 ) -> Result<(String, String), CustomReisIOFailure> {
-    let key = parse_key_or_value(key, action_name)?;
+    let key = parse_key(key, action_name, arguments)?;
     let value = parse_key_or_value(value, action_name)?;
     Ok((key, value))
 }
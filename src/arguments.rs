@@ -1,9 +1,67 @@
-#[derive(Debug, PartialEq, Eq)]
+use crate::{
+    constants::GlobalOptionsConstants,
+    environment::{self, Shell},
+    exchange::{ConflictStrategy, ExchangeFormat},
+    failures::CustomReisIOFailure,
+    namespace::{self, NamespaceScope},
+    search::{SearchFields, SearchMode},
+    template::MissingKeyPolicy,
+    time,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ReisbaseActionsArguments {
     Force,
     Help,
     Clipboard,
-    Description,
+    Description(Option<String>),
+    /// Seconds the entry lives for, from the moment it is written.
+    Ttl(u64),
+    /// Timestamp at which the entry expires.
+    Expires(u64),
+    SearchMode(SearchMode),
+    Keys,
+    Values,
+    Descriptions,
+    IgnoreCase,
+    /// Reads the value of the entry from the file at this path.
+    FromFile(String),
+    /// Reads the value of the entry from the clipboard.
+    FromClipboard,
+    /// Asks for the value of the entry without echoing it.
+    Hidden,
+    /// Seconds after which a copied value is removed from the clipboard.
+    ClearAfter(u64),
+    /// Marks the entry as secret, so its value is masked when shown.
+    Secret,
+    /// Shows the value of a secret entry.
+    Reveal,
+    /// The namespace the keys of the action are in.
+    Namespace(String),
+    /// Applies the action to the entries of every namespace.
+    AllNamespaces,
+    /// Shows which layer answered a read, and the ones it shadows.
+    Explain,
+    /// Shows the entries of every layer, instead of only the ones of the database in use.
+    Merged,
+    /// Writes to the project configuration instead of the user one.
+    Project,
+    /// The format entries are imported from or exported to.
+    Format(ExchangeFormat),
+    /// What an import does with the keys that already exist.
+    OnConflict(ConflictStrategy),
+    /// Shows what an import would change, without changing anything.
+    DryRun,
+    /// Added before the name of every variable set from an entry.
+    Prefix(String),
+    /// The shell the variables set from the entries are exported for.
+    Shell(Shell),
+    /// The command to run, along with its arguments: everything after `--`.
+    Command(Vec<String>),
+    /// What rendering a template does with the keys that don't exist.
+    OnMissing(MissingKeyPolicy),
+    /// The value rendered in place of the keys that don't exist.
+    DefaultValue(String),
 }
 
 impl ReisbaseActionsArguments {
@@ -12,10 +70,383 @@ impl ReisbaseActionsArguments {
             "-f" => Some(ReisbaseActionsArguments::Force),
             "-h" => Some(ReisbaseActionsArguments::Help),
             "-c" => Some(ReisbaseActionsArguments::Clipboard),
-            "-d" => Some(ReisbaseActionsArguments::Description),
+            "-d" => Some(ReisbaseActionsArguments::Description(None)),
+            "-i" => Some(ReisbaseActionsArguments::IgnoreCase),
+            "--keys" => Some(ReisbaseActionsArguments::Keys),
+            "--values" => Some(ReisbaseActionsArguments::Values),
+            "--descriptions" => Some(ReisbaseActionsArguments::Descriptions),
+            "--from-clipboard" => Some(ReisbaseActionsArguments::FromClipboard),
+            "--hidden" => Some(ReisbaseActionsArguments::Hidden),
+            "--secret" => Some(ReisbaseActionsArguments::Secret),
+            "--reveal" => Some(ReisbaseActionsArguments::Reveal),
+            "--all" => Some(ReisbaseActionsArguments::AllNamespaces),
+            "--explain" => Some(ReisbaseActionsArguments::Explain),
+            "--merged" => Some(ReisbaseActionsArguments::Merged),
+            "--project" => Some(ReisbaseActionsArguments::Project),
+            "--dry-run" => Some(ReisbaseActionsArguments::DryRun),
             _ => None,
         }
     }
+
+    pub fn is_argument(argument: &str) -> bool {
+        ReisbaseActionsArguments::new(argument).is_some()
+            || matches!(
+                argument,
+                "--ttl"
+                    | "--expires"
+                    | "--mode"
+                    | "--from-file"
+                    | "--clear-after"
+                    | "--ns"
+                    | "--format"
+                    | "--on-conflict"
+                    | "--prefix"
+                    | "--shell"
+                    | "--on-missing"
+                    | "--default"
+            )
+            || argument == GlobalOptionsConstants::END_OF_OPTIONS
+    }
+
+    /// Whether this argument tells where the value of an entry is read from, instead of
+    /// passing the value itself.
+    pub fn is_value_source(&self) -> bool {
+        matches!(
+            self,
+            ReisbaseActionsArguments::FromFile(_)
+                | ReisbaseActionsArguments::FromClipboard
+                | ReisbaseActionsArguments::Hidden
+        )
+    }
+
+    /// Parses the arguments of an action. Arguments that take a value consume the one that
+    /// follows them, unless it is itself an argument. Unknown arguments are ignored, and every
+    /// argument after `--` is kept as the command to run.
+    pub fn parse(
+        arguments: Vec<String>,
+    ) -> Result<Vec<ReisbaseActionsArguments>, CustomReisIOFailure> {
+        let mut arguments = arguments.into_iter().peekable();
+        let mut parsed = Vec::new();
+
+        while let Some(argument) = arguments.next() {
+            match argument.as_str() {
+                "-d" => {
+                    let description =
+                        arguments.next_if(|next| !ReisbaseActionsArguments::is_argument(next));
+                    parsed.push(ReisbaseActionsArguments::Description(description));
+                }
+                "--ttl" => {
+                    let ttl = parse_value(&argument, arguments.next(), time::parse_duration)?;
+                    parsed.push(ReisbaseActionsArguments::Ttl(ttl));
+                }
+                "--expires" => {
+                    let expires = parse_value(&argument, arguments.next(), time::parse_datetime)?;
+                    parsed.push(ReisbaseActionsArguments::Expires(expires));
+                }
+                "--mode" => {
+                    let mode = parse_value(&argument, arguments.next(), SearchMode::new)?;
+                    parsed.push(ReisbaseActionsArguments::SearchMode(mode));
+                }
+                "--from-file" => {
+                    let path =
+                        parse_value(&argument, arguments.next(), |path| Some(path.to_owned()))?;
+                    parsed.push(ReisbaseActionsArguments::FromFile(path));
+                }
+                "--clear-after" => {
+                    let seconds = parse_value(&argument, arguments.next(), time::parse_duration)?;
+                    parsed.push(ReisbaseActionsArguments::ClearAfter(seconds));
+                }
+                "--ns" => {
+                    let name = parse_value(&argument, arguments.next(), |name| {
+                        namespace::validate(name).ok().map(String::from)
+                    })?;
+                    parsed.push(ReisbaseActionsArguments::Namespace(name));
+                }
+                "--format" => {
+                    let format = parse_value(&argument, arguments.next(), ExchangeFormat::new)?;
+                    parsed.push(ReisbaseActionsArguments::Format(format));
+                }
+                "--on-conflict" => {
+                    let strategy = parse_value(&argument, arguments.next(), ConflictStrategy::new)?;
+                    parsed.push(ReisbaseActionsArguments::OnConflict(strategy));
+                }
+                "--prefix" => {
+                    let prefix = parse_value(&argument, arguments.next(), |prefix| {
+                        Some(prefix.to_owned())
+                            .filter(|prefix| environment::is_valid_prefix(prefix))
+                    })?;
+                    parsed.push(ReisbaseActionsArguments::Prefix(prefix));
+                }
+                "--shell" => {
+                    let shell = parse_value(&argument, arguments.next(), Shell::new)?;
+                    parsed.push(ReisbaseActionsArguments::Shell(shell));
+                }
+                "--on-missing" => {
+                    let policy = parse_value(&argument, arguments.next(), MissingKeyPolicy::new)?;
+                    parsed.push(ReisbaseActionsArguments::OnMissing(policy));
+                }
+                "--default" => {
+                    let value =
+                        parse_value(&argument, arguments.next(), |value| Some(value.to_owned()))?;
+                    parsed.push(ReisbaseActionsArguments::DefaultValue(value));
+                }
+                GlobalOptionsConstants::END_OF_OPTIONS => {
+                    parsed.push(ReisbaseActionsArguments::Command(arguments.collect()));
+                    break;
+                }
+                _ => parsed.extend(ReisbaseActionsArguments::new(&argument)),
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    /// Reverses [`ReisbaseActionsArguments::parse`], so the argument can be passed to a new
+    /// operation.
+    pub fn to_args(&self) -> Vec<String> {
+        match self {
+            ReisbaseActionsArguments::Force => vec![String::from("-f")],
+            ReisbaseActionsArguments::Help => vec![String::from("-h")],
+            ReisbaseActionsArguments::Clipboard => vec![String::from("-c")],
+            ReisbaseActionsArguments::Description(description) => {
+                let mut args = vec![String::from("-d")];
+                args.extend(description.clone());
+                args
+            }
+            ReisbaseActionsArguments::Ttl(ttl) => {
+                vec![String::from("--ttl"), ttl.to_string()]
+            }
+            ReisbaseActionsArguments::Expires(expires) => {
+                vec![String::from("--expires"), time::format_datetime(*expires)]
+            }
+            ReisbaseActionsArguments::SearchMode(mode) => {
+                vec![String::from("--mode"), mode.to_string()]
+            }
+            ReisbaseActionsArguments::Keys => vec![String::from("--keys")],
+            ReisbaseActionsArguments::Values => vec![String::from("--values")],
+            ReisbaseActionsArguments::Descriptions => vec![String::from("--descriptions")],
+            ReisbaseActionsArguments::IgnoreCase => vec![String::from("-i")],
+            ReisbaseActionsArguments::FromFile(path) => {
+                vec![String::from("--from-file"), path.clone()]
+            }
+            ReisbaseActionsArguments::FromClipboard => vec![String::from("--from-clipboard")],
+            ReisbaseActionsArguments::Hidden => vec![String::from("--hidden")],
+            ReisbaseActionsArguments::ClearAfter(seconds) => {
+                vec![String::from("--clear-after"), seconds.to_string()]
+            }
+            ReisbaseActionsArguments::Secret => vec![String::from("--secret")],
+            ReisbaseActionsArguments::Reveal => vec![String::from("--reveal")],
+            ReisbaseActionsArguments::Namespace(name) => {
+                vec![String::from("--ns"), name.clone()]
+            }
+            ReisbaseActionsArguments::AllNamespaces => vec![String::from("--all")],
+            ReisbaseActionsArguments::Explain => vec![String::from("--explain")],
+            ReisbaseActionsArguments::Merged => vec![String::from("--merged")],
+            ReisbaseActionsArguments::Project => vec![String::from("--project")],
+            ReisbaseActionsArguments::Format(format) => {
+                vec![String::from("--format"), format.to_string()]
+            }
+            ReisbaseActionsArguments::OnConflict(strategy) => {
+                vec![String::from("--on-conflict"), strategy.to_string()]
+            }
+            ReisbaseActionsArguments::DryRun => vec![String::from("--dry-run")],
+            ReisbaseActionsArguments::Prefix(prefix) => {
+                vec![String::from("--prefix"), prefix.clone()]
+            }
+            ReisbaseActionsArguments::Shell(shell) => {
+                vec![String::from("--shell"), shell.to_string()]
+            }
+            ReisbaseActionsArguments::Command(command) => {
+                let mut args = vec![String::from(GlobalOptionsConstants::END_OF_OPTIONS)];
+                args.extend(command.iter().cloned());
+                args
+            }
+            ReisbaseActionsArguments::OnMissing(policy) => {
+                vec![String::from("--on-missing"), policy.to_string()]
+            }
+            ReisbaseActionsArguments::DefaultValue(value) => {
+                vec![String::from("--default"), value.clone()]
+            }
+        }
+    }
+}
+
+fn parse_value<T, F>(
+    argument: &str,
+    value: Option<String>,
+    parse: F,
+) -> Result<T, CustomReisIOFailure>
+where
+    F: FnOnce(&str) -> Option<T>,
+{
+    let value = value.ok_or_else(|| CustomReisIOFailure::missing_argument_value(argument))?;
+    parse(&value).ok_or_else(|| CustomReisIOFailure::invalid_argument_value(argument, &value))
+}
+
+/// Lookups over the arguments passed to an action.
+pub trait ReisbaseActionsArgumentsList {
+    /// Returns the description passed with `-d`, if any.
+    fn description(&self) -> Option<&str>;
+    /// Returns true if `-d` was passed, with or without a description.
+    fn has_description_flag(&self) -> bool;
+    /// Returns the timestamp at which an entry written at `now` should expire, if `--ttl` or
+    /// `--expires` was passed.
+    fn expiration(&self, now: u64) -> Option<u64>;
+    /// Returns the search mode passed with `--mode`, or the default one.
+    fn search_mode(&self) -> SearchMode;
+    /// Returns the fields selected with `--keys`, `--values` and `--descriptions`, or all of
+    /// them if none was selected.
+    fn search_fields(&self) -> SearchFields;
+    /// Returns the seconds passed with `--clear-after`, if any.
+    fn clear_after(&self) -> Option<u64>;
+    /// Returns the namespace passed with `--ns`, if any.
+    fn namespace(&self) -> Option<&str>;
+    /// Returns the format passed with `--format`, if any.
+    fn exchange_format(&self) -> Option<ExchangeFormat>;
+    /// Returns the strategy passed with `--on-conflict`, or the default one.
+    fn conflict_strategy(&self) -> ConflictStrategy;
+    /// Returns the prefix passed with `--prefix`, if any.
+    fn prefix(&self) -> Option<&str>;
+    /// Returns the shell passed with `--shell`, or the default one.
+    fn shell(&self) -> Shell;
+    /// Returns the command passed after `--`, if any.
+    fn command(&self) -> Option<&[String]>;
+    /// Returns the value passed with `--default`, if any.
+    fn default_value(&self) -> Option<&str>;
+    /// Returns the policy passed with `--on-missing`. Without one, missing keys get the
+    /// default value if `--default` was passed, and fail otherwise.
+    fn missing_key_policy(&self) -> MissingKeyPolicy;
+    /// Returns the entries an action on many of them applies to: every entry with `--all`,
+    /// the ones of the namespace passed with `--ns`, or else the ones without a namespace.
+    /// Every action on many entries uses this scope, so none reaches another namespace
+    /// unless asked to.
+    fn namespace_scope(&self) -> NamespaceScope;
+}
+
+impl ReisbaseActionsArgumentsList for [ReisbaseActionsArguments] {
+    fn description(&self) -> Option<&str> {
+        self.iter().find_map(|argument| match argument {
+            ReisbaseActionsArguments::Description(description) => description.as_deref(),
+            _ => None,
+        })
+    }
+
+    fn has_description_flag(&self) -> bool {
+        self.iter()
+            .any(|argument| matches!(argument, ReisbaseActionsArguments::Description(_)))
+    }
+
+    fn expiration(&self, now: u64) -> Option<u64> {
+        self.iter().find_map(|argument| match argument {
+            ReisbaseActionsArguments::Ttl(ttl) => Some(now.saturating_add(*ttl)),
+            ReisbaseActionsArguments::Expires(expires) => Some(*expires),
+            _ => None,
+        })
+    }
+
+    fn search_mode(&self) -> SearchMode {
+        self.iter()
+            .find_map(|argument| match argument {
+                ReisbaseActionsArguments::SearchMode(mode) => Some(*mode),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    fn search_fields(&self) -> SearchFields {
+        let fields = SearchFields {
+            keys: self.contains(&ReisbaseActionsArguments::Keys),
+            values: self.contains(&ReisbaseActionsArguments::Values),
+            descriptions: self.contains(&ReisbaseActionsArguments::Descriptions),
+        };
+        if fields.keys || fields.values || fields.descriptions {
+            fields
+        } else {
+            SearchFields::all()
+        }
+    }
+
+    fn clear_after(&self) -> Option<u64> {
+        self.iter().find_map(|argument| match argument {
+            ReisbaseActionsArguments::ClearAfter(seconds) => Some(*seconds),
+            _ => None,
+        })
+    }
+
+    fn namespace(&self) -> Option<&str> {
+        self.iter().find_map(|argument| match argument {
+            ReisbaseActionsArguments::Namespace(name) => Some(name.as_str()),
+            _ => None,
+        })
+    }
+
+    fn exchange_format(&self) -> Option<ExchangeFormat> {
+        self.iter().find_map(|argument| match argument {
+            ReisbaseActionsArguments::Format(format) => Some(*format),
+            _ => None,
+        })
+    }
+
+    fn conflict_strategy(&self) -> ConflictStrategy {
+        self.iter()
+            .find_map(|argument| match argument {
+                ReisbaseActionsArguments::OnConflict(strategy) => Some(*strategy),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    fn prefix(&self) -> Option<&str> {
+        self.iter().find_map(|argument| match argument {
+            ReisbaseActionsArguments::Prefix(prefix) => Some(prefix.as_str()),
+            _ => None,
+        })
+    }
+
+    fn shell(&self) -> Shell {
+        self.iter()
+            .find_map(|argument| match argument {
+                ReisbaseActionsArguments::Shell(shell) => Some(*shell),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    fn command(&self) -> Option<&[String]> {
+        self.iter().find_map(|argument| match argument {
+            ReisbaseActionsArguments::Command(command) => Some(command.as_slice()),
+            _ => None,
+        })
+    }
+
+    fn default_value(&self) -> Option<&str> {
+        self.iter().find_map(|argument| match argument {
+            ReisbaseActionsArguments::DefaultValue(value) => Some(value.as_str()),
+            _ => None,
+        })
+    }
+
+    fn missing_key_policy(&self) -> MissingKeyPolicy {
+        self.iter()
+            .find_map(|argument| match argument {
+                ReisbaseActionsArguments::OnMissing(policy) => Some(*policy),
+                _ => None,
+            })
+            .unwrap_or_else(|| match self.default_value() {
+                Some(_) => MissingKeyPolicy::Default,
+                None => MissingKeyPolicy::Fail,
+            })
+    }
+
+    fn namespace_scope(&self) -> NamespaceScope {
+        if self.contains(&ReisbaseActionsArguments::AllNamespaces) {
+            NamespaceScope::All
+        } else {
+            self.namespace()
+                .map(|name| NamespaceScope::Named(name.to_owned()))
+                .unwrap_or_default()
+        }
+    }
 }
 
 impl std::fmt::Display for ReisbaseActionsArguments {
@@ -24,7 +455,71 @@ impl std::fmt::Display for ReisbaseActionsArguments {
             ReisbaseActionsArguments::Force => write!(f, "-f (Force)"),
             ReisbaseActionsArguments::Help => write!(f, "-h (Help)"),
             ReisbaseActionsArguments::Clipboard => write!(f, "-c (Copy to Clipboard)"),
-            ReisbaseActionsArguments::Description => write!(f, "-d (Description)"),
+            ReisbaseActionsArguments::Description(_) => write!(f, "-d (Description)"),
+            ReisbaseActionsArguments::Ttl(_) => write!(f, "--ttl (Time to Live)"),
+            ReisbaseActionsArguments::Expires(_) => write!(f, "--expires (Expiration Date)"),
+            ReisbaseActionsArguments::SearchMode(_) => write!(f, "--mode (Search Mode)"),
+            ReisbaseActionsArguments::Keys => write!(f, "--keys (Search Keys)"),
+            ReisbaseActionsArguments::Values => write!(f, "--values (Search Values)"),
+            ReisbaseActionsArguments::Descriptions => {
+                write!(f, "--descriptions (Search Descriptions)")
+            }
+            ReisbaseActionsArguments::IgnoreCase => write!(f, "-i (Ignore Case)"),
+            ReisbaseActionsArguments::FromFile(_) => {
+                write!(f, "--from-file (Read Value from File)")
+            }
+            ReisbaseActionsArguments::FromClipboard => {
+                write!(f, "--from-clipboard (Read Value from Clipboard)")
+            }
+            ReisbaseActionsArguments::Hidden => write!(f, "--hidden (Ask for Hidden Value)"),
+            ReisbaseActionsArguments::ClearAfter(_) => {
+                write!(f, "--clear-after (Clear Clipboard After)")
+            }
+            ReisbaseActionsArguments::Secret => write!(f, "--secret (Secret Value)"),
+            ReisbaseActionsArguments::Reveal => write!(f, "--reveal (Reveal Secret Value)"),
+            ReisbaseActionsArguments::Namespace(_) => write!(f, "--ns (Namespace)"),
+            ReisbaseActionsArguments::AllNamespaces => write!(f, "--all (All Namespaces)"),
+            ReisbaseActionsArguments::Explain => write!(f, "--explain (Explain Lookup)"),
+            ReisbaseActionsArguments::Merged => write!(f, "--merged (Merge Layers)"),
+            ReisbaseActionsArguments::Project => write!(f, "--project (Project Configuration)"),
+            ReisbaseActionsArguments::Format(_) => write!(f, "--format (Exchange Format)"),
+            ReisbaseActionsArguments::OnConflict(_) => {
+                write!(f, "--on-conflict (Conflict Strategy)")
+            }
+            ReisbaseActionsArguments::DryRun => write!(f, "--dry-run (Preview Changes)"),
+            ReisbaseActionsArguments::Prefix(_) => write!(f, "--prefix (Variable Prefix)"),
+            ReisbaseActionsArguments::Shell(_) => write!(f, "--shell (Shell)"),
+            ReisbaseActionsArguments::Command(_) => write!(f, "-- (Command)"),
+            ReisbaseActionsArguments::OnMissing(_) => {
+                write!(f, "--on-missing (Missing Key Policy)")
+            }
+            ReisbaseActionsArguments::DefaultValue(_) => write!(f, "--default (Default Value)"),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scope(arguments: &[&str]) -> NamespaceScope {
+        let arguments = arguments
+            .iter()
+            .map(|argument| argument.to_string())
+            .collect();
+        ReisbaseActionsArguments::parse(arguments)
+            .expect("the arguments are valid")
+            .namespace_scope()
+    }
+
+    #[test]
+    fn actions_default_to_the_default_namespace() {
+        assert_eq!(scope(&[]), NamespaceScope::Default);
+        assert_eq!(
+            scope(&["--ns", "db"]),
+            NamespaceScope::Named(String::from("db"))
+        );
+        assert_eq!(scope(&["--all"]), NamespaceScope::All);
+        assert_eq!(scope(&["--all", "--ns", "db"]), NamespaceScope::All);
+    }
+}
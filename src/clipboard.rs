@@ -0,0 +1,478 @@
+//! Copies to and pastes from the clipboard through the first backend that works, so copying
+//! also works over SSH and on machines without a display server.
+
+use std::{
+    env,
+    fs::OpenOptions,
+    io::{self, IsTerminal, Write},
+    path::Path,
+    process::{Command, Stdio},
+    thread,
+    time::Duration,
+};
+
+use base64::prelude::{Engine, BASE64_STANDARD};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    constants::{ClipboardConstants, GlobalOptionsConstants},
+    failures::CustomReisIOFailure,
+};
+
+/// A way of reaching the clipboard.
+pub trait ClipboardBackend {
+    fn name(&self) -> String;
+    fn set_text(&mut self, text: &str) -> io::Result<()>;
+    fn get_text(&mut self) -> io::Result<String>;
+}
+
+/// The clipboard of the display server, through the `arboard` crate.
+#[derive(Debug)]
+pub struct ArboardClipboard;
+
+impl ClipboardBackend for ArboardClipboard {
+    fn name(&self) -> String {
+        String::from("arboard")
+    }
+
+    fn set_text(&mut self, text: &str) -> io::Result<()> {
+        arboard::Clipboard::new()
+            .and_then(|mut clipboard| clipboard.set_text(text))
+            .map_err(|error| io::Error::other(error.to_string()))
+    }
+
+    fn get_text(&mut self) -> io::Result<String> {
+        arboard::Clipboard::new()
+            .and_then(|mut clipboard| clipboard.get_text())
+            .map_err(|error| io::Error::other(error.to_string()))
+    }
+}
+
+/// The clipboard of the terminal emulator, set through the OSC 52 escape sequence. Works
+/// over SSH, as long as the terminal supports it. The clipboard can't be read this way.
+#[derive(Debug)]
+pub struct Osc52Clipboard;
+
+impl ClipboardBackend for Osc52Clipboard {
+    fn name(&self) -> String {
+        String::from("osc52")
+    }
+
+    fn set_text(&mut self, text: &str) -> io::Result<()> {
+        let mut sequence = format!("\x1b]52;c;{}\x07", BASE64_STANDARD.encode(text));
+        // tmux only passes escape sequences through to the terminal when they are wrapped.
+        if env::var_os("TMUX").is_some() {
+            sequence = format!("\x1bPtmux;\x1b{}\x1b\\", sequence);
+        }
+
+        match OpenOptions::new().write(true).open("/dev/tty") {
+            Ok(mut terminal) => terminal.write_all(sequence.as_bytes()),
+            Err(_) if io::stderr().is_terminal() => io::stderr().write_all(sequence.as_bytes()),
+            Err(error) => Err(error),
+        }
+    }
+
+    fn get_text(&mut self) -> io::Result<String> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "the clipboard can't be read through OSC 52",
+        ))
+    }
+}
+
+/// A clipboard reached by running external commands, such as `wl-copy` and `wl-paste`.
+#[derive(Debug)]
+pub struct CommandClipboard {
+    /// Command that receives the text to copy on its stdin.
+    copy: Vec<String>,
+    /// Command that writes the contents of the clipboard to its stdout, if there is one.
+    paste: Option<Vec<String>>,
+}
+
+impl CommandClipboard {
+    /// The commands set in the `REISBASE_CLIPBOARD_COPY` and `REISBASE_CLIPBOARD_PASTE`
+    /// environment variables, if the first one is set.
+    pub fn configured() -> Option<CommandClipboard> {
+        let copy = command_from_env(ClipboardConstants::COPY_COMMAND_ENV_VAR)?;
+        Some(CommandClipboard {
+            copy,
+            paste: command_from_env(ClipboardConstants::PASTE_COMMAND_ENV_VAR),
+        })
+    }
+
+    /// The first known clipboard command that is installed and usable in this session.
+    pub fn detect() -> Option<CommandClipboard> {
+        KNOWN_COMMANDS
+            .iter()
+            .find(|known| {
+                known
+                    .session
+                    .is_none_or(|variable| env::var_os(variable).is_some())
+                    && is_installed(known.copy[0])
+            })
+            .map(|known| CommandClipboard {
+                copy: known.copy.iter().map(|arg| arg.to_string()).collect(),
+                paste: known
+                    .paste
+                    .map(|paste| paste.iter().map(|arg| arg.to_string()).collect()),
+            })
+    }
+}
+
+/// A clipboard command that may be installed.
+struct KnownCommand {
+    /// Environment variable set in the sessions where the command works, if it needs one.
+    session: Option<&'static str>,
+    copy: &'static [&'static str],
+    paste: Option<&'static [&'static str]>,
+}
+
+const KNOWN_COMMANDS: [KnownCommand; 5] = [
+    KnownCommand {
+        session: Some("WAYLAND_DISPLAY"),
+        copy: &["wl-copy"],
+        paste: Some(&["wl-paste", "--no-newline"]),
+    },
+    KnownCommand {
+        session: Some("DISPLAY"),
+        copy: &["xclip", "-selection", "clipboard"],
+        paste: Some(&["xclip", "-selection", "clipboard", "-o"]),
+    },
+    KnownCommand {
+        session: Some("DISPLAY"),
+        copy: &["xsel", "--clipboard", "--input"],
+        paste: Some(&["xsel", "--clipboard", "--output"]),
+    },
+    KnownCommand {
+        session: None,
+        copy: &["pbcopy"],
+        paste: Some(&["pbpaste"]),
+    },
+    KnownCommand {
+        session: None,
+        copy: &["clip.exe"],
+        paste: None,
+    },
+];
+
+impl ClipboardBackend for CommandClipboard {
+    fn name(&self) -> String {
+        self.copy[0].clone()
+    }
+
+    fn set_text(&mut self, text: &str) -> io::Result<()> {
+        let mut child = Command::new(&self.copy[0])
+            .args(&self.copy[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        check_status(&self.copy[0], child.wait()?)
+    }
+
+    fn get_text(&mut self) -> io::Result<String> {
+        let paste = self.paste.as_ref().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("{} has no command to read the clipboard", self.copy[0]),
+            )
+        })?;
+        let output = Command::new(&paste[0])
+            .args(&paste[1..])
+            .stderr(Stdio::null())
+            .output()?;
+        check_status(&paste[0], output.status)?;
+        String::from_utf8(output.stdout)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+}
+
+/// Which backend is used to reach the clipboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ClipboardSelection {
+    /// Tries a configured command, `arboard`, a known command and OSC 52, in this order.
+    #[default]
+    Auto,
+    Arboard,
+    Osc52,
+    /// A configured command, or else the first known one that is installed.
+    Command,
+}
+
+impl ClipboardSelection {
+    pub fn new(selection: &str) -> Option<ClipboardSelection> {
+        match selection {
+            "auto" => Some(ClipboardSelection::Auto),
+            "arboard" => Some(ClipboardSelection::Arboard),
+            "osc52" => Some(ClipboardSelection::Osc52),
+            "command" => Some(ClipboardSelection::Command),
+            _ => None,
+        }
+    }
+
+    /// The backends to try, from the preferred one.
+    pub fn backends(&self) -> Vec<Box<dyn ClipboardBackend>> {
+        let mut backends: Vec<Box<dyn ClipboardBackend>> = Vec::new();
+        match self {
+            ClipboardSelection::Auto => {
+                backends.extend(boxed(CommandClipboard::configured()));
+                backends.push(Box::new(ArboardClipboard));
+                backends.extend(boxed(CommandClipboard::detect()));
+                backends.push(Box::new(Osc52Clipboard));
+            }
+            ClipboardSelection::Arboard => backends.push(Box::new(ArboardClipboard)),
+            ClipboardSelection::Osc52 => backends.push(Box::new(Osc52Clipboard)),
+            ClipboardSelection::Command => backends.extend(boxed(
+                CommandClipboard::configured().or_else(CommandClipboard::detect),
+            )),
+        }
+        backends
+    }
+}
+
+impl std::fmt::Display for ClipboardSelection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClipboardSelection::Auto => write!(f, "auto"),
+            ClipboardSelection::Arboard => write!(f, "arboard"),
+            ClipboardSelection::Osc52 => write!(f, "osc52"),
+            ClipboardSelection::Command => write!(f, "command"),
+        }
+    }
+}
+
+/// What the restore helper needs to put the previous contents back in the clipboard. It is
+/// passed through the helper's stdin, so the values don't show up in the process list.
+#[derive(Debug, Serialize, Deserialize)]
+struct PendingRestore {
+    copied: String,
+    /// The contents of the clipboard before copying, if they could be read.
+    previous: Option<String>,
+    seconds: u64,
+}
+
+/// Copies `text` through the first selected backend that works.
+pub fn copy(selection: ClipboardSelection, text: &str) -> Result<(), CustomReisIOFailure> {
+    first_working(&mut selection.backends(), |backend| backend.set_text(text))
+}
+
+/// Reads the clipboard through the first selected backend that works.
+pub fn paste(selection: ClipboardSelection) -> Result<String, CustomReisIOFailure> {
+    first_working(&mut selection.backends(), |backend| backend.get_text())
+}
+
+/// Copies `text`, and starts a detached helper that restores the previous contents of the
+/// clipboard after `seconds`, unless something else was copied meanwhile. Fails after
+/// copying if the clipboard can't be read back, such as through OSC 52, since the helper
+/// couldn't tell whether the value is still there.
+pub fn copy_temporarily(
+    selection: ClipboardSelection,
+    text: &str,
+    seconds: u64,
+) -> Result<(), CustomReisIOFailure> {
+    let previous = paste(selection).ok();
+    let backend = first_working(&mut selection.backends(), |backend| {
+        backend.set_text(text).map(|()| backend.name())
+    })?;
+    if paste(selection).ok().as_deref() != Some(text) {
+        return Err(CustomReisIOFailure::clipboard_clear_unavailable(&backend));
+    }
+    let restore = PendingRestore {
+        copied: text.to_owned(),
+        previous,
+        seconds,
+    };
+    spawn_restore_helper(selection, &restore).map_err(CustomReisIOFailure::clipboard_helper_failure)
+}
+
+/// Runs the restore helper started by [`copy_temporarily`]. The previous contents are only
+/// restored if the clipboard can be read and still holds the copied value.
+pub fn restore_after_delay(selection: ClipboardSelection) -> io::Result<()> {
+    let restore: PendingRestore = serde_json::from_reader(io::stdin())?;
+    thread::sleep(Duration::from_secs(restore.seconds));
+
+    let current = paste(selection).ok();
+    if current.as_deref() == Some(restore.copied.as_str()) {
+        copy(selection, restore.previous.as_deref().unwrap_or_default())
+            .map_err(|error| io::Error::other(error.error_message().message().to_owned()))?;
+    }
+    Ok(())
+}
+
+fn spawn_restore_helper(selection: ClipboardSelection, restore: &PendingRestore) -> io::Result<()> {
+    let mut command = Command::new(env::current_exe()?);
+    command
+        .arg(GlobalOptionsConstants::CLIPBOARD)
+        .arg(selection.to_string())
+        .arg(ClipboardConstants::RESTORE_HELPER_ACTION)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    // Keeps the helper out of the terminal's process group, so it outlives Ctrl-C.
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+
+    let mut child = command.spawn()?;
+    if let Some(stdin) = child.stdin.take() {
+        serde_json::to_writer(stdin, restore)?;
+    }
+    Ok(())
+}
+
+/// Runs `f` with each of `backends` in order, until it succeeds with one of them.
+fn first_working<T, F>(
+    backends: &mut [Box<dyn ClipboardBackend>],
+    mut f: F,
+) -> Result<T, CustomReisIOFailure>
+where
+    F: FnMut(&mut dyn ClipboardBackend) -> io::Result<T>,
+{
+    let mut failures = Vec::new();
+    for backend in backends {
+        match f(backend.as_mut()) {
+            Ok(result) => return Ok(result),
+            Err(error) => failures.push(format!("{}: {}", backend.name(), error)),
+        }
+    }
+    if failures.is_empty() {
+        failures.push(String::from(
+            "no clipboard command is configured or installed",
+        ));
+    }
+    Err(CustomReisIOFailure::clipboard_unavailable(&failures))
+}
+
+fn boxed(backend: Option<CommandClipboard>) -> Option<Box<dyn ClipboardBackend>> {
+    backend.map(|backend| Box::new(backend) as Box<dyn ClipboardBackend>)
+}
+
+fn command_from_env(variable: &str) -> Option<Vec<String>> {
+    let command = env::var(variable).ok()?;
+    let command = command
+        .split_whitespace()
+        .map(String::from)
+        .collect::<Vec<String>>();
+    if command.is_empty() {
+        None
+    } else {
+        Some(command)
+    }
+}
+
+fn is_installed(program: &str) -> bool {
+    env::var_os("PATH")
+        .map(|path| env::split_paths(&path).any(|dir| Path::new(&dir).join(program).is_file()))
+        .unwrap_or(false)
+}
+
+fn check_status(program: &str, status: std::process::ExitStatus) -> io::Result<()> {
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "{} exited with {}",
+            program, status
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A backend that fails to do anything, like a clipboard command that isn't running.
+    struct BrokenClipboard;
+
+    impl ClipboardBackend for BrokenClipboard {
+        fn name(&self) -> String {
+            String::from("broken")
+        }
+
+        fn set_text(&mut self, _text: &str) -> io::Result<()> {
+            Err(io::Error::other("no display"))
+        }
+
+        fn get_text(&mut self) -> io::Result<String> {
+            Err(io::Error::other("no display"))
+        }
+    }
+
+    /// A clipboard only kept in memory, which leaves the system clipboard untouched.
+    #[derive(Debug, Default)]
+    struct MemoryClipboard {
+        contents: Option<String>,
+    }
+
+    impl ClipboardBackend for MemoryClipboard {
+        fn name(&self) -> String {
+            String::from("memory")
+        }
+
+        fn set_text(&mut self, text: &str) -> io::Result<()> {
+            self.contents = Some(text.to_owned());
+            Ok(())
+        }
+
+        fn get_text(&mut self) -> io::Result<String> {
+            self.contents
+                .clone()
+                .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "the clipboard is empty"))
+        }
+    }
+
+    fn memory(contents: Option<&str>) -> Box<dyn ClipboardBackend> {
+        Box::new(MemoryClipboard {
+            contents: contents.map(String::from),
+        })
+    }
+
+    #[test]
+    fn text_is_copied_through_the_first_backend_that_works() {
+        let mut backends = vec![Box::new(BrokenClipboard), memory(None), memory(None)];
+        first_working(&mut backends, |backend| backend.set_text("copied")).unwrap();
+
+        assert_eq!(backends[1].get_text().unwrap(), "copied");
+        assert!(backends[2].get_text().is_err());
+    }
+
+    #[test]
+    fn text_is_pasted_from_the_first_backend_that_works() {
+        let mut backends = vec![
+            Box::new(BrokenClipboard),
+            memory(None),
+            memory(Some("first")),
+            memory(Some("second")),
+        ];
+        let pasted = first_working(&mut backends, |backend| backend.get_text()).unwrap();
+
+        assert_eq!(pasted, "first");
+    }
+
+    #[test]
+    fn every_failure_is_reported_when_no_backend_works() {
+        let mut backends = vec![Box::new(BrokenClipboard), memory(None)];
+        let error = first_working(&mut backends, |backend| backend.get_text()).unwrap_err();
+
+        assert_eq!(error.kind(), "invalid_platform_operation");
+        assert_eq!(
+            error.error_message().error().to_string(),
+            "broken: no display; memory: the clipboard is empty"
+        );
+    }
+
+    #[test]
+    fn missing_backends_are_reported() {
+        let error = first_working(&mut [], |backend| backend.set_text("copied")).unwrap_err();
+
+        assert_eq!(
+            error.error_message().error().to_string(),
+            "no clipboard command is configured or installed"
+        );
+    }
+}
@@ -0,0 +1,388 @@
+//! The configuration files, which change the defaults of the global options.
+//!
+//! The user configuration is read from `$XDG_CONFIG_HOME/reisbase/config.toml`, and the
+//! nearest [`ConfigConstants::PROJECT_FILE_NAME`] file, found by walking up from the current
+//! directory, overrides it for a project. Environment variables and command line options
+//! still take precedence over both.
+//!
+//! ```toml
+//! database = "secrets.db"  # relative to the file it is set in, unless --local or --global
+//! output = "json"          # plain, json or tsv
+//! copy = true              # get copies every value, as with -c
+//! confirm = "ask"          # ask, yes or no
+//! color = "auto"           # auto, always or never
+//! secret_clear_after = "1m"  # how long secrets stay copied, or 0 to keep them
+//!
+//! [aliases]
+//! gc = "get -c"
+//! ```
+//!
+//! Aliases are expanded into the action and arguments they are set to when they are used
+//! in place of an action. They can't replace the name of an existing action.
+//!
+//! A project file comes with the project, so whoever can change the project can change it.
+//! It may only set `output` and `color`: the settings that change which database is used,
+//! what is confirmed, how long values stay copied or what an alias runs are only read from
+//! the user configuration, and are ignored with a warning in a project file.
+
+use std::{
+    collections::BTreeMap,
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+use toml::{Table, Value};
+
+use crate::{
+    actions::ReisbaseAction,
+    constants::{the_setting_is_ignored_in_projects, ClipboardConstants, ConfigConstants},
+    error_handler::ErrorHandler,
+    failures::CustomReisIOFailure,
+    global_options::{ColorChoice, Confirmation, ReisbaseGlobalOptions},
+    output::OutputFormat,
+    time,
+};
+
+/// Which configuration file a setting is set in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigScope {
+    User,
+    Project,
+}
+
+impl std::fmt::Display for ConfigScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigScope::User => write!(f, "user"),
+            ConfigScope::Project => write!(f, "project"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Setting {
+    #[default]
+    Database,
+    Output,
+    Copy,
+    Confirm,
+    Color,
+    /// How long copied secret values stay in the clipboard, unless `--clear-after` is passed.
+    SecretClearAfter,
+    /// An alias in the `aliases` table, written as `aliases.<name>`.
+    Alias(String),
+}
+
+impl Setting {
+    pub fn new(name: &str) -> Result<Setting, CustomReisIOFailure> {
+        let alias = name
+            .strip_prefix(ConfigConstants::ALIASES_TABLE)
+            .and_then(|alias| alias.strip_prefix('.'));
+        match (name, alias) {
+            ("database", _) => Ok(Setting::Database),
+            ("output", _) => Ok(Setting::Output),
+            ("copy", _) => Ok(Setting::Copy),
+            ("confirm", _) => Ok(Setting::Confirm),
+            ("color", _) => Ok(Setting::Color),
+            ("secret_clear_after", _) => Ok(Setting::SecretClearAfter),
+            (_, Some(alias)) if !alias.is_empty() => Ok(Setting::Alias(alias.to_owned())),
+            _ => Err(CustomReisIOFailure::unknown_setting(name)),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Setting::Database => String::from("database"),
+            Setting::Output => String::from("output"),
+            Setting::Copy => String::from("copy"),
+            Setting::Confirm => String::from("confirm"),
+            Setting::Color => String::from("color"),
+            Setting::SecretClearAfter => String::from("secret_clear_after"),
+            Setting::Alias(alias) => format!("{}.{}", ConfigConstants::ALIASES_TABLE, alias),
+        }
+    }
+
+    /// Parses `value` as written on the command line into the value stored in the file,
+    /// failing if it isn't valid for this setting.
+    pub fn parse(&self, value: &str) -> Result<Value, CustomReisIOFailure> {
+        let parsed = match self {
+            Setting::Copy => value.parse().ok().map(Value::Boolean),
+            _ => Some(Value::String(value.to_owned())),
+        };
+        parsed
+            .filter(|parsed| self.is_valid(parsed))
+            .ok_or_else(|| CustomReisIOFailure::invalid_setting_value(&self.name(), value))
+    }
+
+    /// Whether the setting is only read from the user configuration, as described in the
+    /// [module docs](self).
+    pub fn is_user_only(&self) -> bool {
+        matches!(
+            self,
+            Setting::Database
+                | Setting::Copy
+                | Setting::Confirm
+                | Setting::SecretClearAfter
+                | Setting::Alias(_)
+        )
+    }
+
+    fn is_valid(&self, value: &Value) -> bool {
+        match (self, value) {
+            (Setting::Database, Value::String(path)) => !path.is_empty(),
+            (Setting::Output, Value::String(format)) => OutputFormat::new(format).is_some(),
+            (Setting::Copy, Value::Boolean(_)) => true,
+            (Setting::Confirm, Value::String(policy)) => Confirmation::new(policy).is_some(),
+            (Setting::Color, Value::String(choice)) => ColorChoice::new(choice).is_some(),
+            (Setting::SecretClearAfter, Value::String(duration)) => {
+                time::parse_duration(duration).is_some()
+            }
+            (Setting::SecretClearAfter, Value::Integer(seconds)) => *seconds >= 0,
+            (Setting::Alias(alias), Value::String(expansion)) => {
+                ReisbaseAction::first(alias).is_none()
+                    && expansion
+                        .split_whitespace()
+                        .next()
+                        .is_some_and(|action| ReisbaseAction::first(action).is_some())
+            }
+            _ => false,
+        }
+    }
+}
+
+/// A setting along with the value it has and the file it is set in.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConfiguredSetting {
+    pub name: String,
+    pub value: String,
+    pub scope: ConfigScope,
+    #[serde(skip)]
+    setting: Setting,
+    /// The directory of the file the setting is set in, which relative paths start from.
+    #[serde(skip)]
+    directory: PathBuf,
+}
+
+impl ConfiguredSetting {
+    /// `setting` set to `value` in the file of `scope`, which is in `directory`.
+    pub fn new(
+        setting: Setting,
+        value: &str,
+        scope: ConfigScope,
+        directory: &Path,
+    ) -> ConfiguredSetting {
+        ConfiguredSetting {
+            name: setting.name(),
+            value: value.to_owned(),
+            scope,
+            setting,
+            directory: directory.to_path_buf(),
+        }
+    }
+}
+
+/// The settings of every configuration file, with the ones of the project overriding the
+/// ones of the user.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Configuration {
+    settings: BTreeMap<String, ConfiguredSetting>,
+    /// Why each setting that was ignored isn't used.
+    warnings: Vec<String>,
+}
+
+impl Configuration {
+    /// Reads the configuration files that exist, failing if any of them isn't valid.
+    pub fn load() -> Result<Configuration, CustomReisIOFailure> {
+        let mut configuration = Configuration::default();
+        let files = [
+            (user_config_path(), ConfigScope::User),
+            (find_project_config(), ConfigScope::Project),
+        ];
+        for (path, scope) in files {
+            if let Some(path) = path {
+                configuration.read(&path, scope)?;
+            }
+        }
+        Ok(configuration)
+    }
+
+    /// Every setting that is set, sorted by name.
+    pub fn settings(&self) -> Vec<ConfiguredSetting> {
+        self.settings.values().cloned().collect()
+    }
+
+    /// Why each setting that is set but ignored isn't used.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    pub fn get(&self, setting: &Setting) -> Option<ConfiguredSetting> {
+        self.settings.get(&setting.name()).cloned()
+    }
+
+    /// Changes `options` to the values set in the configuration.
+    pub fn apply(&self, options: &mut ReisbaseGlobalOptions) {
+        for configured in self.settings.values() {
+            let value = configured.value.as_str();
+            match &configured.setting {
+                Setting::Database => {
+                    let path = configured.directory.join(value);
+                    options.configured_database = Some(path.to_string_lossy().into_owned());
+                }
+                Setting::Output => options.output = OutputFormat::new(value).unwrap_or_default(),
+                Setting::Copy => options.copy = value == "true",
+                Setting::Confirm => {
+                    options.confirmation = Confirmation::new(value).unwrap_or_default()
+                }
+                Setting::Color => options.color = ColorChoice::new(value).unwrap_or_default(),
+                Setting::SecretClearAfter => {
+                    options.secret_clear_after = time::parse_duration(value)
+                        .unwrap_or(ClipboardConstants::SECRET_CLEAR_AFTER)
+                }
+                Setting::Alias(alias) => {
+                    options.aliases.insert(alias.clone(), value.to_owned());
+                }
+            }
+        }
+    }
+
+    fn read(&mut self, path: &Path, scope: ConfigScope) -> Result<(), CustomReisIOFailure> {
+        let Some(table) = read_table(path)? else {
+            return Ok(());
+        };
+        let directory = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        for (name, value) in flatten(&table) {
+            let setting = Setting::new(&name)
+                .ok()
+                .filter(|setting| setting.is_valid(value))
+                .ok_or_else(|| CustomReisIOFailure::invalid_config_setting(path, &name))?;
+            if scope == ConfigScope::Project && setting.is_user_only() {
+                self.warnings.push(the_setting_is_ignored_in_projects(
+                    &name,
+                    &path.to_string_lossy(),
+                ));
+                continue;
+            }
+            let value = match value {
+                Value::String(value) => value.clone(),
+                value => value.to_string(),
+            };
+            self.settings.insert(
+                name,
+                ConfiguredSetting::new(setting, &value, scope, &directory),
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Sets `setting` to `value` in the configuration file of `scope`, creating it if needed.
+/// Returns the path of the file.
+pub fn set(
+    setting: &Setting,
+    value: &str,
+    scope: ConfigScope,
+) -> Result<String, CustomReisIOFailure> {
+    if scope == ConfigScope::Project && setting.is_user_only() {
+        return Err(CustomReisIOFailure::user_only_setting(&setting.name()));
+    }
+    let value = setting.parse(value)?;
+    let path = match scope {
+        ConfigScope::User => {
+            user_config_path().ok_or_else(CustomReisIOFailure::config_unavailable)?
+        }
+        ConfigScope::Project => match find_project_config() {
+            Some(path) => path,
+            None => env::current_dir()
+                .map_err(CustomReisIOFailure::current_directory_unavailable)?
+                .join(ConfigConstants::PROJECT_FILE_NAME),
+        },
+    };
+
+    let mut table = read_table(&path)?.unwrap_or_default();
+    match setting {
+        Setting::Alias(alias) => {
+            let aliases = table
+                .entry(ConfigConstants::ALIASES_TABLE)
+                .or_insert_with(|| Value::Table(Table::new()));
+            match aliases {
+                Value::Table(aliases) => {
+                    aliases.insert(alias.clone(), value);
+                }
+                _ => {
+                    return Err(CustomReisIOFailure::invalid_config_setting(
+                        &path,
+                        &setting.name(),
+                    ))
+                }
+            }
+        }
+        _ => {
+            table.insert(setting.name(), value);
+        }
+    }
+
+    write_table(&path, &table).map_err(ErrorHandler::handle_io_error)?;
+    Ok(path.to_string_lossy().into_owned())
+}
+
+/// Returns every setting in `table` by its name, with the aliases under their table.
+fn flatten(table: &Table) -> Vec<(String, &Value)> {
+    table
+        .iter()
+        .flat_map(|(name, value)| match value {
+            Value::Table(aliases) if name == ConfigConstants::ALIASES_TABLE => aliases
+                .iter()
+                .map(|(alias, value)| (format!("{}.{}", name, alias), value))
+                .collect(),
+            value => vec![(name.clone(), value)],
+        })
+        .collect()
+}
+
+/// Reads the table of the configuration file at `path`, or [`None`] if it doesn't exist.
+fn read_table(path: &Path) -> Result<Option<Table>, CustomReisIOFailure> {
+    match fs::read_to_string(path) {
+        Ok(contents) => contents
+            .parse::<Table>()
+            .map(Some)
+            .map_err(|error| CustomReisIOFailure::invalid_config(path, error)),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(ErrorHandler::handle_io_error(error)),
+    }
+}
+
+fn write_table(path: &Path, table: &Table) -> io::Result<()> {
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory)?;
+    }
+    let contents = toml::to_string(table).map_err(io::Error::other)?;
+    fs::write(path, contents)
+}
+
+fn user_config_path() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .filter(|path| Path::new(path).is_absolute())
+        .map(PathBuf::from)
+        .or_else(|| {
+            env::var_os("HOME")
+                .filter(|home| !home.is_empty())
+                .map(|home| Path::new(&home).join(".config"))
+        })
+        .map(|config_home| {
+            config_home
+                .join(ConfigConstants::DIRECTORY)
+                .join(ConfigConstants::FILE_NAME)
+        })
+}
+
+/// Returns the nearest project configuration, walking up from the current directory.
+fn find_project_config() -> Option<PathBuf> {
+    env::current_dir()
+        .ok()?
+        .ancestors()
+        .map(|directory| directory.join(ConfigConstants::PROJECT_FILE_NAME))
+        .find(|path| path.is_file())
+}
@@ -1,13 +1,122 @@
+use std::borrow::Borrow;
+
+use crate::{
+    config::ConfiguredSetting,
+    entry::{HistoryVersion, ReisbaseEntry},
+    exchange::ImportChange,
+    location::DatabaseLocation,
+    namespace::NamespaceSummary,
+    time,
+};
+
 pub struct DatabaseStringConstants;
 
 impl DatabaseStringConstants {
     pub const DATABASE_NAME: &str = "reis.db";
+    /// The name of a local database, found by walking up from the current directory.
+    pub const LOCAL_DATABASE_NAME: &str = ".reis.db";
+    /// The directory of the global database, inside `$XDG_DATA_HOME`.
+    pub const GLOBAL_DATABASE_DIRECTORY: &str = "reisbase";
+    pub const DATABASE_PATH_ENV_VAR: &str = "REISBASE_DB";
+    /// The read-only database below every other one, unless `REISBASE_SYSTEM_DB` is set.
+    pub const SYSTEM_DATABASE_PATH: &str = "/etc/reisbase/reis.db";
+    pub const SYSTEM_DATABASE_PATH_ENV_VAR: &str = "REISBASE_SYSTEM_DB";
+    pub const BACKUP_EXTENSION: &str = ".bak";
+    pub const TEMPORARY_EXTENSION: &str = ".tmp";
+    pub const QUARANTINE_EXTENSION: &str = ".quarantine";
+    /// Starts the first line of a database file, followed by [`Self::FORMAT_VERSION`].
+    pub const FORMAT_HEADER: &str = "#reisbase:";
+    /// The version of the format databases are written in, bumped with every new tag.
+    pub const FORMAT_VERSION: u32 = 7;
+    pub const ENCRYPTED_HEADER: &str = "#reisbase-encrypted:1";
+    pub const PASSPHRASE_ENV_VAR: &str = "REISBASE_PASSPHRASE";
+    pub const NEW_PASSPHRASE_ENV_VAR: &str = "REISBASE_NEW_PASSPHRASE";
     pub const KEY_IDENTIFIER: &str = "#-#";
     pub const VALUE_IDENTIFIER: &str = "#$#";
     pub const DESCRIPTION_IDENTIFIER: &str = "#&#";
+    pub const EXPIRATION_IDENTIFIER: &str = "#@#";
+    pub const HISTORY_IDENTIFIER: &str = "#~#";
+    pub const REPLACED_AT_IDENTIFIER: &str = "#^#";
+    pub const UNDO_IDENTIFIER: &str = "#<#";
+    pub const SECRET_IDENTIFIER: &str = "#!#";
+    pub const NAMESPACE_IDENTIFIER: &str = "#%#";
+    pub const NAMESPACE_SEPARATOR: char = ':';
     pub const ENTRIES_SEPARATOR: &str = "\t";
 }
 
+/// How many previous versions are kept for each key.
+pub const HISTORY_LIMIT: usize = 10;
+
+pub struct GlobalOptionsConstants;
+
+impl GlobalOptionsConstants {
+    pub const DATABASE: &str = "--db";
+    pub const OUTPUT: &str = "--output";
+    pub const YES: &str = "--yes";
+    pub const NO: &str = "--no";
+    pub const NON_INTERACTIVE: &str = "--non-interactive";
+    pub const CLIPBOARD: &str = "--clipboard";
+    pub const LOCAL: &str = "--local";
+    pub const GLOBAL: &str = "--global";
+    /// Ends the options, leaving every argument after it to the command of the run action.
+    pub const END_OF_OPTIONS: &str = "--";
+}
+
+pub struct ConfigConstants;
+
+impl ConfigConstants {
+    /// The directory of the user configuration, inside `$XDG_CONFIG_HOME`.
+    pub const DIRECTORY: &str = "reisbase";
+    pub const FILE_NAME: &str = "config.toml";
+    /// The name of a project configuration, found by walking up from the current directory.
+    pub const PROJECT_FILE_NAME: &str = ".reis.toml";
+    pub const ALIASES_TABLE: &str = "aliases";
+}
+
+pub struct ClipboardConstants;
+
+impl ClipboardConstants {
+    pub const BACKEND_ENV_VAR: &str = "REISBASE_CLIPBOARD";
+    pub const COPY_COMMAND_ENV_VAR: &str = "REISBASE_CLIPBOARD_COPY";
+    pub const PASTE_COMMAND_ENV_VAR: &str = "REISBASE_CLIPBOARD_PASTE";
+    /// Seconds after which copied values are removed from the clipboard, unless
+    /// `--clear-after` is passed.
+    pub const CLEAR_AFTER_ENV_VAR: &str = "REISBASE_CLEAR_AFTER";
+    /// Seconds after which copied secret values are removed from the clipboard, when neither
+    /// `--clear-after` nor `REISBASE_CLEAR_AFTER` are set, unless the `secret_clear_after`
+    /// setting changes it.
+    pub const SECRET_CLEAR_AFTER: u64 = 45;
+    /// The action the detached helper restoring the clipboard is started with.
+    pub const RESTORE_HELPER_ACTION: &str = "__restore-clipboard";
+}
+
+/// The status reisbase exits with, so scripts can branch on the outcome of an operation.
+/// Warnings that are retried after a confirmation exit with the status of the retried
+/// operation instead.
+pub struct ExitCodes;
+
+impl ExitCodes {
+    pub const SUCCESS: u8 = 0;
+    /// An unexpected failure, that doesn't fit any other status.
+    pub const FAILURE: u8 = 1;
+    /// The arguments of the action were missing or invalid.
+    pub const INVALID_ARGUMENTS: u8 = 2;
+    pub const UNKNOWN_ACTION: u8 = 3;
+    /// The key, or the version of it that was requested, doesn't exist.
+    pub const KEY_NOT_FOUND: u8 = 4;
+    pub const ALREADY_EXISTS: u8 = 5;
+    /// The database has no entries, or none that matched the request.
+    pub const EMPTY_DATABASE: u8 = 6;
+    pub const CORRUPT_DATABASE: u8 = 7;
+    /// The database couldn't be read or written, such as for missing permissions or space.
+    pub const IO_FAILURE: u8 = 8;
+    /// The database is encrypted with a different passphrase than the one given.
+    pub const WRONG_PASSPHRASE: u8 = 9;
+    /// The command of the run action was found, but couldn't be started.
+    pub const COMMAND_NOT_EXECUTABLE: u8 = 126;
+    pub const COMMAND_NOT_FOUND: u8 = 127;
+}
+
 pub struct SuccessfulOperationStrings;
 
 impl SuccessfulOperationStrings {
@@ -17,21 +126,332 @@ impl SuccessfulOperationStrings {
             key, value
         )
     }
+    pub fn value_with_description(value: &str, description: Option<&str>) -> String {
+        format!(
+            "{}\nDescription: {}",
+            value,
+            description.unwrap_or("This entry has no description.")
+        )
+    }
     pub fn successful_delete_operation(key: &str) -> String {
         format!("Successfully deleted the entry for {}!", key)
     }
-    pub fn successful_clear_operation() -> String {
-        String::from("Successfully cleared all database values!")
+    pub fn successful_clear_operation(count: usize) -> String {
+        format!("Successfully cleared {} database values!", count)
+    }
+    pub fn entry_expires_in(key: &str, remaining: &str, expires_at: &str) -> String {
+        format!(
+            "The entry {} expires in {} (at {}).",
+            key, remaining, expires_at
+        )
+    }
+    pub fn entry_never_expires(key: &str) -> String {
+        format!("The entry {} never expires.", key)
+    }
+    pub fn entry_history(
+        key: &str,
+        current: Option<&ReisbaseEntry>,
+        versions: &[HistoryVersion],
+    ) -> String {
+        let current = current
+            .map(|entry| entry.value.as_str())
+            .unwrap_or("(deleted)");
+        let versions = versions
+            .iter()
+            .enumerate()
+            .map(|(index, version)| {
+                format!(
+                    "\n  {}: {} (replaced at {})",
+                    index + 1,
+                    version.entry.value,
+                    time::format_datetime(version.replaced_at)
+                )
+            })
+            .collect::<String>();
+        format!("History of {}:\n  current: {}{}", key, current, versions)
+    }
+    pub fn successful_revert_operation(key: &str, value: &str) -> String {
+        format!(
+            "Successfully reverted the key {} to the value {}!",
+            key, value
+        )
+    }
+    pub fn successful_undo_operation(restored: usize) -> String {
+        format!(
+            "Successfully undid the last change, restoring {} entries! Undo again to redo it.",
+            restored
+        )
+    }
+    pub fn successful_repair_operation(quarantined: usize, quarantine_path: &str) -> String {
+        format!(
+            "Successfully repaired the database! {} corrupt lines were moved to {}.",
+            quarantined, quarantine_path
+        )
+    }
+    pub fn nothing_to_repair() -> String {
+        String::from("The database has no corrupt lines, there is nothing to repair!")
+    }
+    pub fn namespaces(namespaces: &[NamespaceSummary]) -> String {
+        namespaces
+            .iter()
+            .map(|namespace| format!("{} ({} entries)", namespace.name, namespace.count))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+    pub fn successful_create_namespace_operation(namespace: &str) -> String {
+        format!("Successfully created the namespace {}!", namespace)
+    }
+    pub fn successful_rename_namespace_operation(
+        namespace: &str,
+        new_namespace: &str,
+        moved: usize,
+    ) -> String {
+        format!(
+            "Successfully renamed the namespace {} to {}, moving {} entries!",
+            namespace, new_namespace, moved
+        )
+    }
+    pub fn successful_drop_namespace_operation(namespace: &str, deleted: usize) -> String {
+        format!(
+            "Successfully dropped the namespace {} along with its {} entries!",
+            namespace, deleted
+        )
+    }
+    /// The value of an entry read through the layers, followed by the layer that answered and
+    /// the ones it shadows.
+    pub fn explained_value(
+        value: &str,
+        layer: &DatabaseLocation,
+        shadowed: &[DatabaseLocation],
+    ) -> String {
+        let mut message = format!(
+            "{}\nAnswered by the {} database at {}.",
+            value, layer.source, layer.path
+        );
+        for layer in shadowed {
+            message.push_str(&format!(
+                "\nShadows the {} database at {}.",
+                layer.source, layer.path
+            ));
+        }
+        message
+    }
+    pub fn command_exited(command: &[String], exit_code: u8) -> String {
+        format!(
+            "The command {} exited with the status {}.",
+            command.join(" "),
+            exit_code
+        )
+    }
+    pub fn configured_settings(settings: &[ConfiguredSetting]) -> String {
+        settings
+            .iter()
+            .map(|configured| {
+                format!(
+                    "{} = {} ({})",
+                    configured.name, configured.value, configured.scope
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+    pub fn successful_config_set_operation(setting: &str, value: &str, path: &str) -> String {
+        format!("Successfully set {} to {} in {}!", setting, value, path)
+    }
+    pub fn successful_import_operation(
+        source: &str,
+        imported: usize,
+        overwritten: usize,
+        skipped: usize,
+    ) -> String {
+        format!(
+            "Successfully imported {} entries from {}, overwriting {} and skipping {} existing ones!",
+            imported, source, overwritten, skipped
+        )
+    }
+    /// The changes an import would make, one per line.
+    pub fn import_preview(changes: &[ImportChange]) -> String {
+        changes
+            .iter()
+            .map(|change| format!("{} {} = {}", change.change, change.key, change.value))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+    pub fn successful_init_operation(path: &str, source: &str) -> String {
+        format!("Successfully created a {} database at {}!", source, path)
+    }
+    pub fn successful_encrypt_operation(db_name: &str) -> String {
+        format!(
+            "Successfully encrypted the database {}! Keep your passphrase safe, it can't be recovered.",
+            db_name
+        )
+    }
+    pub fn successful_decrypt_operation(db_name: &str) -> String {
+        format!(
+            "Successfully decrypted the database {}! It is now stored as plain text.",
+            db_name
+        )
+    }
+    pub fn successful_rekey_operation(db_name: &str) -> String {
+        format!(
+            "Successfully changed the passphrase of the database {}!",
+            db_name
+        )
+    }
+    pub fn successful_restore_backup_operation(backup_path: &str) -> String {
+        format!(
+            "Successfully restored the database from the backup {}!",
+            backup_path
+        )
     }
 }
 
-pub const THIS_ACTION_IS_PERMANENT: &str = "This action is permanent, and will clear all your data. Are you sure you want to continue? (Y/n)";
+pub const THIS_ACTION_IS_PERMANENT: &str =
+    "This action is permanent, and will delete every entry it applies to.";
+pub const ARE_YOU_SURE: &str = "Are you sure you want to continue?";
+/// Shown after every question. Only answers starting with `y` confirm, so an empty answer is
+/// always a no.
+pub const CONFIRMATION_CHOICES: &str = "(y/N)";
+pub const CONFIRMATION_REQUIRED: &str = "Run it again with --yes before the action to confirm it.";
 pub const CANCELED_OPERATION: &str = "The operation was canceled!";
 pub const INPUT_READ_ERROR: &str = "Sorry, an error occured when attempting to read your input!";
 pub const EMPTY_DATABASE: &str = "Database doesn't contain any value!";
+pub const NOTHING_TO_UNDO: &str = "There is no change to undo!";
+pub const ALREADY_ENCRYPTED: &str =
+    "The database is already encrypted! You can change its passphrase with the rekey action.";
+pub const NOT_ENCRYPTED: &str =
+    "The database is not encrypted! You can encrypt it with the encrypt action.";
+pub const NO_NAMESPACES: &str =
+    "Database doesn't contain any namespace! You can create one with the ns-create action.";
+pub const NO_SETTINGS: &str =
+    "No setting is configured! You can set one with the config-set action.";
+pub const NO_MATCHING_ENTRIES: &str = "No entry in the database matches your search!";
+
+/// Shown instead of the value of a secret entry.
+pub const MASKED_VALUE: &str = "********";
+
+pub const DO_YOU_WANT_TO_REPLACE_IT: &str = "Do you want to replace it?";
+pub const DO_YOU_WANT_TO_RUN_IT: &str = "Do you want to run it instead?";
 
 pub fn the_key_already_exists(key: &str, old_value: &str) -> String {
-    format!("The key {} already exists in this database, with the value of {}. Do you want to replace it? (Y/n)", key, old_value)
+    format!(
+        "The key {} already exists in this database, with the value of {}.",
+        key, old_value
+    )
+}
+
+pub fn the_legacy_database_is_ignored(legacy_path: &str, path: &str) -> String {
+    format!(
+        "The database {} in the current directory is no longer used, and {} is used instead! You can keep using it by renaming it to {}, or by passing it with --db.",
+        legacy_path,
+        path,
+        DatabaseStringConstants::LOCAL_DATABASE_NAME
+    )
+}
+
+pub fn the_layer_is_skipped(path: &str, reason: &str) -> String {
+    format!(
+        "The database {} below this one is skipped, since it can't be read! {}",
+        path, reason
+    )
+}
+
+pub fn the_setting_is_ignored_in_projects(setting: &str, path: &str) -> String {
+    format!(
+        "The setting {} in the project configuration {} is ignored, since it can only be set in the user configuration!",
+        setting, path
+    )
+}
+
+pub fn the_database_already_exists(path: &str) -> String {
+    format!("A database already exists at {}!", path)
+}
+
+pub fn the_namespace_already_exists(namespace: &str) -> String {
+    format!(
+        "The namespace {} already exists in this database!",
+        namespace
+    )
+}
+
+pub fn the_namespace_does_not_exists(namespace: &str) -> String {
+    format!(
+        "The namespace {} does not exists! You can list the namespaces with the command: namespaces",
+        namespace
+    )
+}
+
+pub fn the_setting_is_not_set(setting: &str) -> String {
+    format!(
+        "The setting {} is not configured! You can set it with the command: config-set {} value",
+        setting, setting
+    )
+}
+
+pub fn the_import_has_conflicts(source: &str, imported: usize, keys: &[&str]) -> String {
+    format!(
+        "Imported {} entries from {}, but some of its keys already exist in this database: {}.",
+        imported,
+        source,
+        keys.join(", ")
+    )
+}
+
+pub fn the_import_failed_on_conflicts(source: &str, keys: &[&str]) -> String {
+    format!(
+        "Nothing was imported from {}, since some of its keys already exist in this database: {}! You can choose what to do with them with --on-conflict skip, overwrite or prompt.",
+        source,
+        keys.join(", ")
+    )
+}
+
+pub fn the_variables_conflict(variable: &str, keys: &[String]) -> String {
+    format!(
+        "The keys {} would both be set as the variable {}! You can rename one of them, or pick the entries to set with --ns.",
+        keys.join(" and "),
+        variable
+    )
+}
+
+pub fn the_variable_name_is_invalid(key: &str) -> String {
+    format!(
+        "The key {} can't be set as a variable, since it has no letter nor digit! You can rename it, or pick the entries to set with --ns.",
+        key
+    )
+}
+
+pub fn the_template_keys_are_missing(source: &str, keys: &[String]) -> String {
+    format!(
+        "The keys {} used in {} don't exist in this database! You can render a value in their place with --default, or keep their placeholders with --on-missing leave.",
+        keys.join(", "),
+        source
+    )
+}
+
+pub fn the_secrets_are_not_revealed(keys: &[String]) -> String {
+    format!(
+        "The keys {} are secret, so their values are only written with --reveal!",
+        keys.join(", ")
+    )
+}
+
+pub fn the_action_requires_arguments(action: &str, arguments: &[String]) -> String {
+    format!(
+        "The {} action requires the {} argument!",
+        action,
+        arguments.join(", ")
+    )
+}
+
+pub fn the_key_has_no_history(key: &str) -> String {
+    format!("The key {} has no previous versions!", key)
+}
+
+pub fn the_history_version_does_not_exists(key: &str, version: usize) -> String {
+    format!(
+        "The key {} has no version {}! You can list its versions with the command: history {}",
+        key, version, key
+    )
 }
 
 pub fn the_entry_does_not_exists(key: &str, value: &str) -> String {
@@ -40,3 +460,18 @@ pub fn the_entry_does_not_exists(key: &str, value: &str) -> String {
         key, key, value
     )
 }
+
+pub fn did_you_mean<S: Borrow<str>>(suggestions: &[S]) -> String {
+    format!("Did you mean {}?", suggestions.join(", "))
+}
+
+pub fn do_you_want_to_run(action: &str, key: &str) -> String {
+    format!("Do you want to run {} {} instead?", action, key)
+}
+
+pub fn did_you_mean_the_action(action: &str, suggestion: &str) -> String {
+    format!(
+        "The action {} doesn't exist. Did you mean {}?",
+        action, suggestion
+    )
+}
@@ -1,8 +1,12 @@
 use crate::{
     actions::ReisbaseAction,
     arguments::ReisbaseActionsArguments,
-    constants::DatabaseStringConstants,
-    failures::{CustomReisActionWarning, CustomReisIOFailure},
+    constants::the_legacy_database_is_ignored,
+    error_handler::ErrorHandler,
+    failures::{CustomFailureOperation, CustomReisIOFailure},
+    global_options::ReisbaseGlobalOptions,
+    layers::{Layer, LayeredView},
+    location::DatabaseLocation,
     reisbase::Reisbase,
     success::CustomSuccessOperation,
 };
@@ -11,6 +15,11 @@ use crate::{
 pub struct Controller {
     pub action: ReisbaseAction,
     pub database: Reisbase,
+    pub location: DatabaseLocation,
+    /// The databases below the one in use, which are only read for the actions that look
+    /// through them.
+    pub layers: Vec<Layer>,
+    pub options: ReisbaseGlobalOptions,
 }
 
 impl Controller {
@@ -19,18 +28,58 @@ impl Controller {
         key: Option<String>,
         value: Option<String>,
         arguments: Vec<String>,
+        options: &ReisbaseGlobalOptions,
     ) -> Result<Controller, CustomReisIOFailure> {
-        let arguments = arguments
-            .iter()
-            .filter_map(|argument| ReisbaseActionsArguments::new(argument))
-            .collect();
+        let arguments = ReisbaseActionsArguments::parse(arguments)?;
 
-        let action = ReisbaseAction::new(action, key, value, arguments)?;
-        let database = Reisbase::build(DatabaseStringConstants::DATABASE_NAME)?;
-        Ok(Controller { action, database })
+        let action = ReisbaseAction::new(action, key, value, arguments, options.clipboard)?;
+        let location = if action.creates_database() {
+            DatabaseLocation::for_init(options)?
+        } else {
+            DatabaseLocation::resolve(options)?
+        };
+        if let Some(legacy_path) = location.ignored_legacy_database(options) {
+            ErrorHandler::print_warning(&the_legacy_database_is_ignored(
+                &legacy_path,
+                &location.path,
+            ));
+        }
+        let database = if !action.needs_database() {
+            Reisbase::detached(&location.path)
+        } else if action.tolerates_corruption() {
+            Reisbase::build_for_repair(&location.path)?
+        } else {
+            Reisbase::build(&location.path)?
+        };
+        let layers = if action.reads_layers() {
+            Layer::below(&location)
+        } else {
+            Vec::new()
+        };
+        Ok(Controller {
+            action,
+            database,
+            location,
+            layers,
+            options: options.clone(),
+        })
     }
 
-    pub fn execute(&mut self) -> Result<CustomSuccessOperation, CustomReisActionWarning> {
-        ReisbaseAction::execute(self)
+    /// The database in use along with the layers below it, for the actions that read through
+    /// them.
+    pub fn layered(&self) -> LayeredView<'_> {
+        LayeredView::new(&self.location, &self.database, &self.layers)
+    }
+
+    /// Executes the requested action and saves any change it made to the database, so a
+    /// failed write is reported instead of being lost. Changes are saved even if the action
+    /// ends with a warning, since some actions, like import, still write part of what they
+    /// were asked to.
+    pub fn execute(&mut self) -> Result<CustomSuccessOperation, CustomFailureOperation> {
+        let result = ReisbaseAction::execute(self);
+        self.database
+            .save()
+            .map_err(CustomFailureOperation::Error)?;
+        result
     }
 }
@@ -0,0 +1,732 @@
+//! Reading and writing of the database file.
+//!
+//! The current format starts with [`DatabaseStringConstants::FORMAT_HEADER`] followed by
+//! its version, and stores one entry per line, as tagged fields separated by
+//! [`DatabaseStringConstants::ENTRIES_SEPARATOR`]:
+//!
+//! ```text
+//! #reisbase:7
+//! #-#key\t#$#value\t#&#description\t#@#expiration\t#!#
+//! ```
+//!
+//! The key and value fields always come first; the fields after them are optional. The
+//! [`DatabaseStringConstants::SECRET_IDENTIFIER`] field has no contents, and marks the entry
+//! as secret. Keys inside a namespace start with its name and
+//! [`DatabaseStringConstants::NAMESPACE_SEPARATOR`], and every namespace is declared on its
+//! own line before the entries, tagged with [`DatabaseStringConstants::NAMESPACE_IDENTIFIER`].
+//! Only declared namespaces hold keys, so the keys of files written before namespaces existed
+//! keep their separators without being moved into one, and empty namespaces are kept. After the
+//! entries come the previous versions of each key, tagged with
+//! [`DatabaseStringConstants::HISTORY_IDENTIFIER`], and the records needed to undo the last
+//! change, tagged with [`DatabaseStringConstants::UNDO_IDENTIFIER`]. An undo record without a
+//! value means the key didn't exist before that change, and one ending with an empty
+//! [`DatabaseStringConstants::NAMESPACE_IDENTIFIER`] field means the key was inside a declared
+//! namespace.
+//!
+//! The version is bumped whenever a tag is added, so a file is only read with the tags of its
+//! version and the older ones, and files written in a newer version are refused instead of
+//! being misread:
+//!
+//! | Version | Tags added                                        |
+//! |---------|---------------------------------------------------|
+//! | 2       | keys and values                                   |
+//! | 3       | descriptions                                      |
+//! | 4       | expirations                                       |
+//! | 5       | previous versions, replacement dates, undo records |
+//! | 6       | secrets                                           |
+//! | 7       | namespaces                                        |
+//!
+//! Field contents are escaped, so keys and values may hold tabs, newlines and the field tags
+//! themselves. Files without the header use the original unescaped `#-#key\tvalue` format and
+//! are still read, then rewritten in the current format on the next save.
+//!
+//! Lines that can't be read are never dropped silently: they are reported as
+//! [`CorruptLine`]s, so the database can refuse to overwrite them until it is repaired.
+
+use std::collections::{BTreeSet, HashMap};
+
+use crate::{
+    constants::DatabaseStringConstants,
+    entry::{HistoryVersion, ReisbaseEntry, UndoRecord},
+};
+
+const ESCAPE: char = '\\';
+
+/// The first line of a database file written in the current format.
+pub fn header() -> String {
+    format!(
+        "{}{}",
+        DatabaseStringConstants::FORMAT_HEADER,
+        DatabaseStringConstants::FORMAT_VERSION
+    )
+}
+
+/// The format version `identifier` was introduced in, as listed in the [module docs](self).
+fn introduced_in(identifier: &str) -> u32 {
+    match identifier {
+        DatabaseStringConstants::DESCRIPTION_IDENTIFIER => 3,
+        DatabaseStringConstants::EXPIRATION_IDENTIFIER => 4,
+        DatabaseStringConstants::HISTORY_IDENTIFIER
+        | DatabaseStringConstants::REPLACED_AT_IDENTIFIER
+        | DatabaseStringConstants::UNDO_IDENTIFIER => 5,
+        DatabaseStringConstants::SECRET_IDENTIFIER => 6,
+        DatabaseStringConstants::NAMESPACE_IDENTIFIER => 7,
+        _ => 2,
+    }
+}
+
+/// Returns `field` without the tag `identifier`, if it starts with it and the tag exists in
+/// `version` of the format.
+fn strip_tag<'a>(field: &'a str, identifier: &str, version: u32) -> Option<&'a str> {
+    field
+        .strip_prefix(identifier)
+        .filter(|_| introduced_in(identifier) <= version)
+}
+
+pub fn serialize(
+    namespaces: &BTreeSet<String>,
+    entries: &HashMap<String, ReisbaseEntry>,
+    history: &HashMap<String, Vec<HistoryVersion>>,
+    undo_journal: &[UndoRecord],
+) -> String {
+    let mut contents = format!("{}\n", header());
+    for namespace in namespaces {
+        contents.push_str(&serialize_record(
+            DatabaseStringConstants::NAMESPACE_IDENTIFIER,
+            namespace,
+            None,
+            None,
+        ));
+    }
+    for key in sorted_keys(entries) {
+        contents.push_str(&serialize_record(
+            DatabaseStringConstants::KEY_IDENTIFIER,
+            key,
+            Some(&entries[key]),
+            None,
+        ));
+    }
+    for key in sorted_keys(history) {
+        for version in &history[key] {
+            contents.push_str(&serialize_record(
+                DatabaseStringConstants::HISTORY_IDENTIFIER,
+                key,
+                Some(&version.entry),
+                Some(version.replaced_at),
+            ));
+        }
+    }
+    for record in undo_journal {
+        contents.push_str(&serialize_undo_record(record));
+    }
+    contents
+}
+
+fn sorted_keys<T>(map: &HashMap<String, T>) -> Vec<&String> {
+    let mut keys = map.keys().collect::<Vec<&String>>();
+    keys.sort();
+    keys
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ParsedDatabase {
+    pub namespaces: BTreeSet<String>,
+    pub entries: HashMap<String, ReisbaseEntry>,
+    pub history: HashMap<String, Vec<HistoryVersion>>,
+    pub undo_journal: Vec<UndoRecord>,
+    pub corrupt_lines: Vec<CorruptLine>,
+}
+
+enum Record {
+    Namespace(String),
+    Entry(String, ReisbaseEntry),
+    History(String, HistoryVersion),
+    Undo(UndoRecord),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorruptLine {
+    /// One-based line number in the database file.
+    pub number: usize,
+    pub content: String,
+    pub reason: CorruptionReason,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CorruptionReason {
+    MissingSeparator,
+    MissingKeyIdentifier,
+    MissingValueIdentifier,
+    UnknownField,
+    InvalidEscapeSequence,
+    InvalidExpiration,
+    InvalidReplacementDate,
+    DuplicateKey,
+}
+
+impl std::fmt::Display for CorruptionReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CorruptionReason::MissingSeparator => {
+                write!(f, "the key and value separator is missing")
+            }
+            CorruptionReason::MissingKeyIdentifier => write!(f, "the key identifier is missing"),
+            CorruptionReason::MissingValueIdentifier => {
+                write!(f, "the value identifier is missing")
+            }
+            CorruptionReason::UnknownField => write!(f, "it contains an unknown field"),
+            CorruptionReason::InvalidEscapeSequence => {
+                write!(f, "it contains an invalid escape sequence")
+            }
+            CorruptionReason::InvalidExpiration => {
+                write!(f, "its expiration is not a valid timestamp")
+            }
+            CorruptionReason::InvalidReplacementDate => {
+                write!(
+                    f,
+                    "its replacement date is missing or not a valid timestamp"
+                )
+            }
+            CorruptionReason::DuplicateKey => write!(f, "its key was already defined"),
+        }
+    }
+}
+
+impl std::fmt::Display for CorruptLine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Line {}: {}", self.number, self.reason)
+    }
+}
+
+/// The version in the header of a file written in a format this reisbase can't read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsupportedVersion(pub String);
+
+/// Reads the records of a database file. Fails if it is written in a newer version of the
+/// format, whose tags might not be understood.
+pub fn deserialize(contents: &str) -> Result<ParsedDatabase, UnsupportedVersion> {
+    let mut lines = contents.lines().enumerate();
+    let header = contents
+        .lines()
+        .next()
+        .and_then(|line| line.strip_prefix(DatabaseStringConstants::FORMAT_HEADER));
+    let version = match header {
+        Some(version) => {
+            lines.next();
+            match version.parse::<u32>() {
+                Ok(number) if (2..=DatabaseStringConstants::FORMAT_VERSION).contains(&number) => {
+                    Some(number)
+                }
+                _ => return Err(UnsupportedVersion(version.to_owned())),
+            }
+        }
+        None => None,
+    };
+    let parse_line = |line: &str| match version {
+        Some(version) => parse_record(line, version),
+        None => parse_legacy_entry(line),
+    };
+
+    let mut parsed = ParsedDatabase::default();
+    for (index, line) in lines.filter(|(_, line)| !line.is_empty()) {
+        let record = parse_line(line).and_then(|record| match record {
+            Record::Entry(ref key, _) if parsed.entries.contains_key(key) => {
+                Err(CorruptionReason::DuplicateKey)
+            }
+            record => Ok(record),
+        });
+
+        match record {
+            Ok(Record::Namespace(namespace)) => {
+                parsed.namespaces.insert(namespace);
+            }
+            Ok(Record::Entry(key, entry)) => {
+                parsed.entries.insert(key, entry);
+            }
+            Ok(Record::History(key, version)) => {
+                parsed.history.entry(key).or_default().push(version);
+            }
+            Ok(Record::Undo(record)) => parsed.undo_journal.push(record),
+            Err(reason) => parsed.corrupt_lines.push(CorruptLine {
+                number: index + 1,
+                content: line.to_owned(),
+                reason,
+            }),
+        }
+    }
+    Ok(parsed)
+}
+
+/// Formats `corrupt_lines` so they can be kept aside for a manual recovery, each preceded by
+/// a comment with its original line number and the reason it was rejected.
+pub fn serialize_quarantine(corrupt_lines: &[CorruptLine]) -> String {
+    corrupt_lines
+        .iter()
+        .map(|line| format!("# {}\n{}\n", line, line.content))
+        .collect()
+}
+
+fn serialize_record(
+    identifier: &str,
+    key: &str,
+    entry: Option<&ReisbaseEntry>,
+    replaced_at: Option<u64>,
+) -> String {
+    let mut line = format!("{}{}", identifier, escape(key));
+    if let Some(entry) = entry {
+        push_entry_fields(&mut line, entry);
+    }
+    if let Some(replaced_at) = replaced_at {
+        push_field(
+            &mut line,
+            DatabaseStringConstants::REPLACED_AT_IDENTIFIER,
+            &replaced_at.to_string(),
+        );
+    }
+    line.push('\n');
+    line
+}
+
+fn serialize_undo_record(record: &UndoRecord) -> String {
+    let mut line = format!(
+        "{}{}",
+        DatabaseStringConstants::UNDO_IDENTIFIER,
+        escape(&record.key)
+    );
+    if let Some(ref entry) = record.previous {
+        push_entry_fields(&mut line, entry);
+    }
+    if record.namespaced {
+        push_field(&mut line, DatabaseStringConstants::NAMESPACE_IDENTIFIER, "");
+    }
+    line.push('\n');
+    line
+}
+
+fn push_entry_fields(line: &mut String, entry: &ReisbaseEntry) {
+    push_field(
+        line,
+        DatabaseStringConstants::VALUE_IDENTIFIER,
+        &entry.value,
+    );
+    if let Some(ref description) = entry.description {
+        push_field(
+            line,
+            DatabaseStringConstants::DESCRIPTION_IDENTIFIER,
+            description,
+        );
+    }
+    if let Some(expires_at) = entry.expires_at {
+        push_field(
+            line,
+            DatabaseStringConstants::EXPIRATION_IDENTIFIER,
+            &expires_at.to_string(),
+        );
+    }
+    if entry.secret {
+        push_field(line, DatabaseStringConstants::SECRET_IDENTIFIER, "");
+    }
+}
+
+fn push_field(line: &mut String, identifier: &str, contents: &str) {
+    line.push_str(DatabaseStringConstants::ENTRIES_SEPARATOR);
+    line.push_str(identifier);
+    line.push_str(&escape(contents));
+}
+
+fn parse_record(line: &str, version: u32) -> Result<Record, CorruptionReason> {
+    let (key, fields) = match line.split_once(DatabaseStringConstants::ENTRIES_SEPARATOR) {
+        Some((key, fields)) => (key, Some(fields)),
+        None => (line, None),
+    };
+
+    if let Some(key) = strip_tag(key, DatabaseStringConstants::KEY_IDENTIFIER, version) {
+        let fields = fields.ok_or(CorruptionReason::MissingSeparator)?;
+        let (entry, replaced_at) = parse_entry_fields(fields, version)?;
+        if replaced_at.is_some() {
+            return Err(CorruptionReason::UnknownField);
+        }
+        Ok(Record::Entry(unescape_field(key)?, entry))
+    } else if let Some(key) = strip_tag(key, DatabaseStringConstants::HISTORY_IDENTIFIER, version) {
+        let fields = fields.ok_or(CorruptionReason::MissingSeparator)?;
+        let (entry, replaced_at) = parse_entry_fields(fields, version)?;
+        let replaced_at = replaced_at.ok_or(CorruptionReason::InvalidReplacementDate)?;
+        Ok(Record::History(
+            unescape_field(key)?,
+            HistoryVersion { entry, replaced_at },
+        ))
+    } else if let Some(namespace) =
+        strip_tag(key, DatabaseStringConstants::NAMESPACE_IDENTIFIER, version)
+    {
+        if fields.is_some() {
+            return Err(CorruptionReason::UnknownField);
+        }
+        Ok(Record::Namespace(unescape_field(namespace)?))
+    } else if let Some(key) = strip_tag(key, DatabaseStringConstants::UNDO_IDENTIFIER, version) {
+        let namespaced_field = format!(
+            "{}{}",
+            DatabaseStringConstants::ENTRIES_SEPARATOR,
+            DatabaseStringConstants::NAMESPACE_IDENTIFIER
+        );
+        let (fields, namespaced) = match fields {
+            _ if version < introduced_in(DatabaseStringConstants::NAMESPACE_IDENTIFIER) => {
+                (fields, false)
+            }
+            Some(DatabaseStringConstants::NAMESPACE_IDENTIFIER) => (None, true),
+            Some(fields) => match fields.strip_suffix(namespaced_field.as_str()) {
+                Some(fields) => (Some(fields), true),
+                None => (Some(fields), false),
+            },
+            None => (None, false),
+        };
+        let previous = match fields {
+            Some(fields) => match parse_entry_fields(fields, version)? {
+                (entry, None) => Some(entry),
+                (_, Some(_)) => return Err(CorruptionReason::UnknownField),
+            },
+            None => None,
+        };
+        Ok(Record::Undo(UndoRecord {
+            key: unescape_field(key)?,
+            previous,
+            namespaced,
+        }))
+    } else {
+        Err(CorruptionReason::MissingKeyIdentifier)
+    }
+}
+
+/// Parses the value and optional fields of a record, returning the entry they describe and
+/// the replacement date, if one was present.
+fn parse_entry_fields(
+    fields: &str,
+    version: u32,
+) -> Result<(ReisbaseEntry, Option<u64>), CorruptionReason> {
+    let mut fields = fields.split(DatabaseStringConstants::ENTRIES_SEPARATOR);
+    let value = fields
+        .next()
+        .and_then(|value| strip_tag(value, DatabaseStringConstants::VALUE_IDENTIFIER, version))
+        .ok_or(CorruptionReason::MissingValueIdentifier)?;
+
+    let mut entry = ReisbaseEntry::new(&unescape_field(value)?);
+    let mut replaced_at = None;
+    for field in fields {
+        if let Some(timestamp) = strip_tag(
+            field,
+            DatabaseStringConstants::REPLACED_AT_IDENTIFIER,
+            version,
+        ) {
+            let timestamp = timestamp
+                .parse::<u64>()
+                .map_err(|_| CorruptionReason::InvalidReplacementDate)?;
+            replaced_at = Some(timestamp);
+        } else {
+            parse_optional_field(field, &mut entry, version)?;
+        }
+    }
+    Ok((entry, replaced_at))
+}
+
+fn parse_optional_field(
+    field: &str,
+    entry: &mut ReisbaseEntry,
+    version: u32,
+) -> Result<(), CorruptionReason> {
+    if let Some(description) = strip_tag(
+        field,
+        DatabaseStringConstants::DESCRIPTION_IDENTIFIER,
+        version,
+    ) {
+        entry.description = Some(unescape_field(description)?);
+        Ok(())
+    } else if let Some(expires_at) = strip_tag(
+        field,
+        DatabaseStringConstants::EXPIRATION_IDENTIFIER,
+        version,
+    ) {
+        let expires_at = expires_at
+            .parse::<u64>()
+            .map_err(|_| CorruptionReason::InvalidExpiration)?;
+        entry.expires_at = Some(expires_at);
+        Ok(())
+    } else if strip_tag(field, DatabaseStringConstants::SECRET_IDENTIFIER, version) == Some("") {
+        entry.secret = true;
+        Ok(())
+    } else {
+        Err(CorruptionReason::UnknownField)
+    }
+}
+
+fn parse_legacy_entry(line: &str) -> Result<Record, CorruptionReason> {
+    let (key, value) = line
+        .split_once(DatabaseStringConstants::ENTRIES_SEPARATOR)
+        .ok_or(CorruptionReason::MissingSeparator)?;
+    let key = key
+        .strip_prefix(DatabaseStringConstants::KEY_IDENTIFIER)
+        .ok_or(CorruptionReason::MissingKeyIdentifier)?;
+    Ok(Record::Entry(key.to_owned(), ReisbaseEntry::new(value)))
+}
+
+fn unescape_field(field: &str) -> Result<String, CorruptionReason> {
+    unescape(field).ok_or(CorruptionReason::InvalidEscapeSequence)
+}
+
+/// Escapes the characters that would break a field apart: backslashes, tabs and line breaks.
+pub fn escape(field: &str) -> String {
+    let mut escaped = String::with_capacity(field.len());
+    for character in field.chars() {
+        match character {
+            ESCAPE => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            _ => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+/// Reverses [`escape`]. Returns [`None`] if `field` holds an unknown or unfinished escape.
+fn unescape(field: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(field.len());
+    let mut characters = field.chars();
+    while let Some(character) = characters.next() {
+        if character != ESCAPE {
+            unescaped.push(character);
+            continue;
+        }
+        match characters.next()? {
+            ESCAPE => unescaped.push(ESCAPE),
+            't' => unescaped.push('\t'),
+            'n' => unescaped.push('\n'),
+            'r' => unescaped.push('\r'),
+            _ => return None,
+        }
+    }
+    Some(unescaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    /// Text made of the characters the format gives meaning to, which may also start with a
+    /// record tag, mixed with arbitrary Unicode.
+    fn field() -> impl Strategy<Value = String> {
+        prop_oneof![
+            any::<String>(),
+            "(#-#|#~#|#<#|#%#|#\\$#)?[\\\\\t\r\n#:a-zé🦀]{0,16}",
+        ]
+    }
+
+    fn entry() -> impl Strategy<Value = ReisbaseEntry> {
+        (
+            field(),
+            proptest::option::of(field()),
+            proptest::option::of(any::<u64>()),
+            any::<bool>(),
+        )
+            .prop_map(|(value, description, expires_at, secret)| ReisbaseEntry {
+                value,
+                description,
+                expires_at,
+                secret,
+            })
+    }
+
+    fn database() -> impl Strategy<Value = ParsedDatabase> {
+        (
+            proptest::collection::btree_set(field(), 0..4),
+            proptest::collection::hash_map(field(), entry(), 0..8),
+            proptest::collection::hash_map(
+                field(),
+                proptest::collection::vec(
+                    (entry(), any::<u64>())
+                        .prop_map(|(entry, replaced_at)| HistoryVersion { entry, replaced_at }),
+                    1..4,
+                ),
+                0..4,
+            ),
+            proptest::collection::vec(
+                (field(), proptest::option::of(entry()), any::<bool>()).prop_map(
+                    |(key, previous, namespaced)| UndoRecord {
+                        key,
+                        previous,
+                        namespaced,
+                    },
+                ),
+                0..4,
+            ),
+        )
+            .prop_map(
+                |(namespaces, entries, history, undo_journal)| ParsedDatabase {
+                    namespaces,
+                    entries,
+                    history,
+                    undo_journal,
+                    corrupt_lines: Vec::new(),
+                },
+            )
+    }
+
+    fn round_trip(database: &ParsedDatabase) -> ParsedDatabase {
+        deserialize(&serialize(
+            &database.namespaces,
+            &database.entries,
+            &database.history,
+            &database.undo_journal,
+        ))
+        .expect("the current version is read")
+    }
+
+    fn parse(contents: &str) -> ParsedDatabase {
+        deserialize(contents).expect("the version is read")
+    }
+
+    proptest! {
+        #[test]
+        fn serialized_databases_are_read_back_unchanged(database in database()) {
+            prop_assert_eq!(round_trip(&database), database);
+        }
+
+        #[test]
+        fn escaped_fields_are_unescaped_unchanged(field in field()) {
+            let escaped = escape(&field);
+            prop_assert!(!escaped.contains(['\t', '\r', '\n']));
+            prop_assert_eq!(unescape(&escaped), Some(field));
+        }
+    }
+
+    #[test]
+    fn keys_starting_with_record_tags_are_read_as_keys() {
+        let entries = ["#-#key", "#~#key", "#<#key", "#%#key", "#-#"]
+            .into_iter()
+            .map(|key| {
+                (
+                    key.to_owned(),
+                    ReisbaseEntry::new("\\value\twith\r\nbreaks\\"),
+                )
+            })
+            .collect::<HashMap<String, ReisbaseEntry>>();
+        let database = ParsedDatabase {
+            entries,
+            ..ParsedDatabase::default()
+        };
+
+        assert_eq!(round_trip(&database), database);
+    }
+
+    #[test]
+    fn legacy_databases_are_read_unescaped() {
+        let parsed = parse("#-#key\tvalue with \\n\tand a tab\n\n#-#other\t#$#value\n");
+
+        assert_eq!(
+            parsed.entries.get("key"),
+            Some(&ReisbaseEntry::new("value with \\n\tand a tab"))
+        );
+        assert_eq!(
+            parsed.entries.get("other"),
+            Some(&ReisbaseEntry::new("#$#value"))
+        );
+        assert!(parsed.corrupt_lines.is_empty());
+    }
+
+    #[test]
+    fn legacy_lines_without_a_separator_are_corrupt() {
+        let parsed = parse("#-#key\tvalue\nkey without separator\n");
+
+        assert_eq!(parsed.entries.len(), 1);
+        assert_eq!(
+            parsed.corrupt_lines,
+            vec![CorruptLine {
+                number: 2,
+                content: String::from("key without separator"),
+                reason: CorruptionReason::MissingSeparator,
+            }]
+        );
+    }
+
+    #[test]
+    fn unescape_rejects_unknown_and_unfinished_escapes() {
+        assert_eq!(unescape("\\x"), None);
+        assert_eq!(unescape("value\\"), None);
+        assert_eq!(unescape("\\\\\\t"), Some(String::from("\\\t")));
+    }
+
+    #[test]
+    fn fields_with_invalid_escapes_make_the_line_corrupt() {
+        let contents = format!(
+            "{}\n#-#good\t#$#value\n#-#bad\\x\t#$#value\n#-#bad\t#$#value\\\n",
+            header()
+        );
+        let parsed = parse(&contents);
+
+        assert_eq!(parsed.entries.len(), 1);
+        assert_eq!(
+            parsed
+                .corrupt_lines
+                .iter()
+                .map(|line| (line.number, line.reason))
+                .collect::<Vec<_>>(),
+            vec![
+                (3, CorruptionReason::InvalidEscapeSequence),
+                (4, CorruptionReason::InvalidEscapeSequence),
+            ]
+        );
+    }
+
+    #[test]
+    fn the_version_is_bumped_with_every_tag() {
+        let tags = [
+            DatabaseStringConstants::KEY_IDENTIFIER,
+            DatabaseStringConstants::VALUE_IDENTIFIER,
+            DatabaseStringConstants::DESCRIPTION_IDENTIFIER,
+            DatabaseStringConstants::EXPIRATION_IDENTIFIER,
+            DatabaseStringConstants::HISTORY_IDENTIFIER,
+            DatabaseStringConstants::REPLACED_AT_IDENTIFIER,
+            DatabaseStringConstants::UNDO_IDENTIFIER,
+            DatabaseStringConstants::SECRET_IDENTIFIER,
+            DatabaseStringConstants::NAMESPACE_IDENTIFIER,
+        ];
+        assert_eq!(
+            tags.into_iter().map(introduced_in).max(),
+            Some(DatabaseStringConstants::FORMAT_VERSION)
+        );
+        assert_eq!(header(), "#reisbase:7");
+    }
+
+    #[test]
+    fn tags_newer_than_the_file_are_unknown() {
+        let parsed = parse("#reisbase:5\n#%#db\n#-#pw\t#$#hunter2\t#!#\n#-#host\t#$#x\t#&#d\n");
+
+        assert_eq!(parsed.entries.len(), 1);
+        assert!(parsed.namespaces.is_empty());
+        assert_eq!(
+            parsed
+                .corrupt_lines
+                .iter()
+                .map(|line| (line.number, line.reason))
+                .collect::<Vec<_>>(),
+            vec![
+                (2, CorruptionReason::MissingKeyIdentifier),
+                (3, CorruptionReason::UnknownField),
+            ]
+        );
+
+        let parsed = parse("#reisbase:6\n#-#pw\t#$#hunter2\t#!#\n#<#pw\t#%#\n");
+        assert!(parsed.entries["pw"].secret);
+        assert_eq!(parsed.corrupt_lines.len(), 1);
+    }
+
+    #[test]
+    fn newer_and_unknown_versions_are_refused() {
+        for version in ["8", "1", "", "two"] {
+            assert_eq!(
+                deserialize(&format!("#reisbase:{}\n#-#key\t#$#value\n", version)),
+                Err(UnsupportedVersion(version.to_owned()))
+            );
+        }
+    }
+}
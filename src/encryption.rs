@@ -0,0 +1,193 @@
+//! Encrypted database files.
+//!
+//! An encrypted database starts with the [`DatabaseStringConstants::ENCRYPTED_HEADER`] line,
+//! followed by a single line of base64 fields separated by tabs: the salt, the passphrase
+//! check, the nonce and the ciphertext. The key is derived from the passphrase and salt with
+//! Argon2id, and encrypts the same contents a plain database holds with XChaCha20-Poly1305.
+//! The passphrase check is derived along with the key, so a wrong passphrase can be told
+//! apart from a damaged file.
+
+use std::env;
+
+use argon2::Argon2;
+use base64::prelude::{Engine, BASE64_STANDARD};
+use chacha20poly1305::{
+    aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng, Payload},
+    XChaCha20Poly1305, XNonce,
+};
+
+use crate::{constants::DatabaseStringConstants, failures::CustomReisIOFailure};
+
+const SALT_LENGTH: usize = 16;
+const KEY_LENGTH: usize = 32;
+const CHECK_LENGTH: usize = 32;
+
+/// The key a database is encrypted with, along with what it was derived from.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Encryption {
+    passphrase: String,
+    salt: [u8; SALT_LENGTH],
+    key: [u8; KEY_LENGTH],
+    check: [u8; CHECK_LENGTH],
+}
+
+impl std::fmt::Debug for Encryption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Encryption").finish_non_exhaustive()
+    }
+}
+
+impl Encryption {
+    /// Derives a new key from `passphrase`, with a random salt.
+    pub fn new(passphrase: &str) -> Result<Encryption, CustomReisIOFailure> {
+        let mut salt = [0; SALT_LENGTH];
+        OsRng.fill_bytes(&mut salt);
+        Encryption::derive(passphrase, salt)
+    }
+
+    fn derive(
+        passphrase: &str,
+        salt: [u8; SALT_LENGTH],
+    ) -> Result<Encryption, CustomReisIOFailure> {
+        let mut derived = [0; KEY_LENGTH + CHECK_LENGTH];
+        Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), &salt, &mut derived)
+            .map_err(|error| CustomReisIOFailure::encryption_failure(&error.to_string()))?;
+
+        let (key, check) = derived.split_at(KEY_LENGTH);
+        Ok(Encryption {
+            passphrase: passphrase.to_owned(),
+            salt,
+            key: key.try_into().expect("the key is KEY_LENGTH bytes long"),
+            check: check
+                .try_into()
+                .expect("the check is CHECK_LENGTH bytes long"),
+        })
+    }
+
+    pub fn passphrase(&self) -> &str {
+        &self.passphrase
+    }
+
+    /// Encrypts the contents of a database into the contents of an encrypted one.
+    pub fn encrypt(&self, contents: &str) -> Result<String, CustomReisIOFailure> {
+        let cipher = XChaCha20Poly1305::new(&self.key.into());
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: contents.as_bytes(),
+                    aad: DatabaseStringConstants::ENCRYPTED_HEADER.as_bytes(),
+                },
+            )
+            .map_err(|error| CustomReisIOFailure::encryption_failure(&error.to_string()))?;
+
+        let fields = [
+            BASE64_STANDARD.encode(self.salt),
+            BASE64_STANDARD.encode(self.check),
+            BASE64_STANDARD.encode(nonce),
+            BASE64_STANDARD.encode(ciphertext),
+        ];
+        Ok(format!(
+            "{}\n{}\n",
+            DatabaseStringConstants::ENCRYPTED_HEADER,
+            fields.join(DatabaseStringConstants::ENTRIES_SEPARATOR)
+        ))
+    }
+}
+
+/// Whether `contents` were read from an encrypted database.
+pub fn is_encrypted(contents: &str) -> bool {
+    contents
+        .lines()
+        .next()
+        .is_some_and(|header| header == DatabaseStringConstants::ENCRYPTED_HEADER)
+}
+
+/// Decrypts the contents of the encrypted database at `db_name` with `passphrase`, returning
+/// them along with the key, so they can be encrypted again when saving.
+pub fn decrypt(
+    db_name: &str,
+    contents: &str,
+    passphrase: &str,
+) -> Result<(String, Encryption), CustomReisIOFailure> {
+    let corrupted = || CustomReisIOFailure::corrupted_encrypted_database(db_name);
+
+    let fields = contents
+        .lines()
+        .nth(1)
+        .ok_or_else(corrupted)?
+        .split(DatabaseStringConstants::ENTRIES_SEPARATOR)
+        .map(|field| BASE64_STANDARD.decode(field))
+        .collect::<Result<Vec<Vec<u8>>, _>>()
+        .map_err(|_| corrupted())?;
+    let [salt, check, nonce, ciphertext] = fields.as_slice() else {
+        return Err(corrupted());
+    };
+    let salt = salt.as_slice().try_into().map_err(|_| corrupted())?;
+    if nonce.len() != XNonce::default().len() {
+        return Err(corrupted());
+    }
+
+    let encryption = Encryption::derive(passphrase, salt)?;
+    if encryption.check.as_slice() != check.as_slice() {
+        return Err(CustomReisIOFailure::wrong_passphrase(db_name));
+    }
+
+    let cipher = XChaCha20Poly1305::new(&encryption.key.into());
+    let plaintext = cipher
+        .decrypt(
+            XNonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: DatabaseStringConstants::ENCRYPTED_HEADER.as_bytes(),
+            },
+        )
+        .map_err(|_| corrupted())?;
+    let contents = String::from_utf8(plaintext).map_err(|_| corrupted())?;
+    Ok((contents, encryption))
+}
+
+/// The passphrase set in `REISBASE_PASSPHRASE`, or else the one the user types in.
+pub fn passphrase(db_name: &str) -> Result<String, CustomReisIOFailure> {
+    match passphrase_from_env(DatabaseStringConstants::PASSPHRASE_ENV_VAR) {
+        Some(passphrase) => Ok(passphrase),
+        None => rpassword::prompt_password(format!("Passphrase for {}: ", db_name))
+            .map_err(CustomReisIOFailure::passphrase_unavailable),
+    }
+}
+
+/// The passphrase to encrypt a plain database with. Since it has no passphrase yet, it can
+/// also be set in `REISBASE_PASSPHRASE`.
+pub fn first_passphrase(db_name: &str) -> Result<String, CustomReisIOFailure> {
+    match passphrase_from_env(DatabaseStringConstants::NEW_PASSPHRASE_ENV_VAR)
+        .or_else(|| passphrase_from_env(DatabaseStringConstants::PASSPHRASE_ENV_VAR))
+    {
+        Some(passphrase) => Ok(passphrase),
+        None => new_passphrase(db_name),
+    }
+}
+
+/// The passphrase to encrypt a database with from now on, set in `REISBASE_NEW_PASSPHRASE`,
+/// or else typed in twice by the user.
+pub fn new_passphrase(db_name: &str) -> Result<String, CustomReisIOFailure> {
+    if let Some(passphrase) = passphrase_from_env(DatabaseStringConstants::NEW_PASSPHRASE_ENV_VAR) {
+        return Ok(passphrase);
+    }
+
+    let passphrase = rpassword::prompt_password(format!("New passphrase for {}: ", db_name))
+        .map_err(CustomReisIOFailure::passphrase_unavailable)?;
+    let confirmation = rpassword::prompt_password("Repeat the new passphrase: ")
+        .map_err(CustomReisIOFailure::passphrase_unavailable)?;
+    if passphrase.is_empty() || passphrase != confirmation {
+        return Err(CustomReisIOFailure::passphrases_dont_match());
+    }
+    Ok(passphrase)
+}
+
+fn passphrase_from_env(variable: &str) -> Option<String> {
+    env::var(variable)
+        .ok()
+        .filter(|passphrase| !passphrase.is_empty())
+}
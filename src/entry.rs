@@ -0,0 +1,113 @@
+use serde::{Serialize, Serializer};
+
+use crate::constants::MASKED_VALUE;
+
+/// A value stored in the database, along with the metadata kept for it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReisbaseEntry {
+    pub value: String,
+    pub description: Option<String>,
+    /// Timestamp after which the entry is no longer visible, and is purged on the next save.
+    pub expires_at: Option<u64>,
+    /// Whether the value is only shown when explicitly revealed.
+    pub secret: bool,
+}
+
+impl ReisbaseEntry {
+    pub fn new(value: &str) -> ReisbaseEntry {
+        ReisbaseEntry {
+            value: value.to_owned(),
+            description: None,
+            expires_at: None,
+            secret: false,
+        }
+    }
+
+    /// Returns a copy of the entry fit to be shown, with its value masked if it is secret.
+    pub fn masked(&self) -> ReisbaseEntry {
+        let mut entry = self.clone();
+        if entry.secret {
+            entry.value = String::from(MASKED_VALUE);
+        }
+        entry
+    }
+
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires_at
+            .map(|expires_at| expires_at <= now)
+            .unwrap_or(false)
+    }
+
+    /// Returns true if the description contains `term`, ignoring case.
+    pub fn description_contains(&self, term: &str) -> bool {
+        self.description
+            .as_ref()
+            .map(|description| description.to_lowercase().contains(&term.to_lowercase()))
+            .unwrap_or(false)
+    }
+}
+
+/// A value that is masked whenever it is shown or serialized, if it belongs to a secret
+/// entry, while still being available to retry the operation it was passed to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SensitiveValue {
+    value: String,
+    secret: bool,
+}
+
+impl SensitiveValue {
+    pub fn new(value: &str, secret: bool) -> SensitiveValue {
+        SensitiveValue {
+            value: value.to_owned(),
+            secret,
+        }
+    }
+
+    /// The value itself, which must not be shown to the user.
+    pub fn reveal(&self) -> &str {
+        &self.value
+    }
+}
+
+impl std::fmt::Display for SensitiveValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.secret {
+            write!(f, "{}", MASKED_VALUE)
+        } else {
+            write!(f, "{}", self.value)
+        }
+    }
+}
+
+impl Serialize for SensitiveValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// An entry along with the key it is stored under.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct KeyedEntry {
+    pub key: String,
+    #[serde(flatten)]
+    pub entry: ReisbaseEntry,
+}
+
+/// A previous version of an entry, kept after it was overwritten or deleted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HistoryVersion {
+    #[serde(flatten)]
+    pub entry: ReisbaseEntry,
+    /// Timestamp at which this version stopped being the current one.
+    pub replaced_at: u64,
+}
+
+/// How a key looked before the last change to the database, so the change can be undone.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UndoRecord {
+    pub key: String,
+    /// The entry the key held, or [`None`] if the key didn't exist.
+    pub previous: Option<ReisbaseEntry>,
+    /// Whether the key was inside a declared namespace, which undoing declares again.
+    pub namespaced: bool,
+}
@@ -0,0 +1,314 @@
+//! Entries as environment variables, for the run and env actions.
+//!
+//! Each entry becomes a variable named after its key: letters are uppercased, and every
+//! character that can't be part of a variable name is replaced with `_`, so `db.url` becomes
+//! `DB_URL`. The namespace of a key is left out when the entries come from a single one, with
+//! `--ns`, and is kept as part of the name with `--all`. A prefix passed with `--prefix` is
+//! added before every name as it is. Keys without any letter or digit can't be named, and are
+//! refused.
+//!
+//! Variables always hold the real values of the entries, so the env action refuses to print
+//! secret entries unless they are revealed.
+
+use std::{
+    collections::BTreeMap,
+    process::{Command, ExitStatus},
+};
+
+use serde::Serialize;
+
+use crate::{
+    constants::ExitCodes,
+    entry::KeyedEntry,
+    failures::{CustomReisActionWarning, CustomReisIOFailure},
+    namespace::{self, NamespaceScope},
+};
+
+/// The shell the statements printed by the env action are written for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Shell {
+    #[default]
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Shell {
+    pub fn new(shell: &str) -> Option<Shell> {
+        match shell {
+            "bash" | "sh" => Some(Shell::Bash),
+            "zsh" => Some(Shell::Zsh),
+            "fish" => Some(Shell::Fish),
+            _ => None,
+        }
+    }
+
+    /// The statement that exports `variable` in this shell.
+    fn export(&self, variable: &EnvironmentVariable) -> String {
+        match self {
+            Shell::Bash | Shell::Zsh => {
+                format!("export {}={}", variable.name, quote_posix(&variable.value))
+            }
+            Shell::Fish => format!("set -gx {} {}", variable.name, quote_fish(&variable.value)),
+        }
+    }
+}
+
+impl std::fmt::Display for Shell {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Shell::Bash => write!(f, "bash"),
+            Shell::Zsh => write!(f, "zsh"),
+            Shell::Fish => write!(f, "fish"),
+        }
+    }
+}
+
+/// An entry along with the name of the variable it is exported as.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EnvironmentVariable {
+    pub name: String,
+    pub key: String,
+    pub value: String,
+}
+
+/// Names the variable of each entry in `scope`, failing if two keys would be exported as the
+/// same variable.
+pub fn variables(
+    entries: Vec<KeyedEntry>,
+    scope: &NamespaceScope,
+    prefix: Option<&str>,
+) -> Result<Vec<EnvironmentVariable>, CustomReisActionWarning> {
+    let mut variables = BTreeMap::<String, EnvironmentVariable>::new();
+    for keyed in entries {
+        let key = match scope {
+            NamespaceScope::Named(_) => namespace::local_key(&keyed.key),
+            _ => keyed.key.as_str(),
+        };
+        let name = variable_name(key, prefix).ok_or_else(|| {
+            CustomReisActionWarning::InvalidVariableName {
+                key: keyed.key.clone(),
+            }
+        })?;
+        if let Some(existing) = variables.get(&name) {
+            return Err(CustomReisActionWarning::ConflictingVariables {
+                variable: name,
+                keys: vec![existing.key.clone(), keyed.key],
+            });
+        }
+        variables.insert(
+            name.clone(),
+            EnvironmentVariable {
+                name,
+                key: keyed.key,
+                value: keyed.entry.value,
+            },
+        );
+    }
+    Ok(variables.into_values().collect())
+}
+
+/// Returns `key` as the name of a variable, after `prefix`, or [`None`] if it has no letter
+/// nor digit to name it by.
+pub fn variable_name(key: &str, prefix: Option<&str>) -> Option<String> {
+    if !key.chars().any(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+    let sanitized = key
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    Some(match prefix {
+        Some(prefix) => format!("{}{}", prefix, sanitized),
+        None if sanitized.starts_with(|c: char| c.is_ascii_digit()) => format!("_{}", sanitized),
+        None => sanitized,
+    })
+}
+
+/// Whether `prefix` can start the name of a variable: it is made of letters, digits and `_`,
+/// and doesn't start with a digit.
+pub fn is_valid_prefix(prefix: &str) -> bool {
+    prefix
+        .chars()
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && prefix
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// The statements exporting every variable in `shell`, one per line.
+pub fn export_statements(shell: Shell, variables: &[EnvironmentVariable]) -> String {
+    variables
+        .iter()
+        .map(|variable| shell.export(variable) + "\n")
+        .collect()
+}
+
+/// Runs `command` with the variables added to the environment of reisbase, waiting for it to
+/// finish. Returns the status it exited with, which is `128` plus the signal if it was killed
+/// by one.
+pub fn run(
+    command: &[String],
+    variables: &[EnvironmentVariable],
+) -> Result<u8, CustomReisIOFailure> {
+    let (program, args) = command
+        .split_first()
+        .ok_or_else(CustomReisIOFailure::missing_command)?;
+    let status = Command::new(program)
+        .args(args)
+        .envs(
+            variables
+                .iter()
+                .map(|variable| (&variable.name, &variable.value)),
+        )
+        .status()
+        .map_err(|error| CustomReisIOFailure::command_failure(program, error))?;
+    Ok(exit_code(status))
+}
+
+fn exit_code(status: ExitStatus) -> u8 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return u8::try_from(128 + signal).unwrap_or(ExitCodes::FAILURE);
+        }
+    }
+    status
+        .code()
+        .and_then(|code| u8::try_from(code).ok())
+        .unwrap_or(ExitCodes::FAILURE)
+}
+
+/// Single quotes `value`, closing the quotes around each single quote it has.
+fn quote_posix(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Single quotes `value`, in which fish only reads `\\` and `\'` as escapes.
+fn quote_fish(value: &str) -> String {
+    format!("'{}'", value.replace('\\', r"\\").replace('\'', r"\'"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::ReisbaseEntry;
+
+    const TRICKY_VALUES: [&str; 6] = [
+        "it's",
+        r"back\slash",
+        "$HOME and `date` and $(date)",
+        "first\nsecond",
+        "'",
+        "",
+    ];
+
+    fn keyed(key: &str, value: &str) -> KeyedEntry {
+        KeyedEntry {
+            key: key.to_owned(),
+            entry: ReisbaseEntry::new(value),
+        }
+    }
+
+    #[test]
+    fn keys_are_named_as_variables() {
+        assert_eq!(variable_name("db.url", None).as_deref(), Some("DB_URL"));
+        assert_eq!(variable_name("db:host", None).as_deref(), Some("DB_HOST"));
+        assert_eq!(variable_name("1st", None).as_deref(), Some("_1ST"));
+        assert_eq!(
+            variable_name("1st", Some("APP_")).as_deref(),
+            Some("APP_1ST")
+        );
+        assert_eq!(variable_name("clé", None).as_deref(), Some("CL_"));
+    }
+
+    #[test]
+    fn keys_without_letters_nor_digits_are_refused() {
+        for key in ["", "-", ":::", "é", " "] {
+            assert_eq!(variable_name(key, None), None, "{:?}", key);
+            assert_eq!(variable_name(key, Some("APP_")), None, "{:?}", key);
+        }
+        assert!(matches!(
+            variables(vec![keyed("--", "value")], &NamespaceScope::Default, None),
+            Err(CustomReisActionWarning::InvalidVariableName { key }) if key == "--"
+        ));
+    }
+
+    #[test]
+    fn namespaces_are_left_out_of_names_within_one() {
+        let scope = NamespaceScope::Named(String::from("db"));
+        let named = variables(vec![keyed("db:host", "localhost")], &scope, None)
+            .expect("the variables are named");
+        assert_eq!(named[0].name, "HOST");
+        assert!(matches!(
+            variables(vec![keyed("db:", "value")], &scope, None),
+            Err(CustomReisActionWarning::InvalidVariableName { key }) if key == "db:"
+        ));
+    }
+
+    #[test]
+    fn prefixes_must_start_a_variable_name() {
+        assert!(is_valid_prefix("APP_"));
+        assert!(is_valid_prefix("_x"));
+        assert!(!is_valid_prefix(""));
+        assert!(!is_valid_prefix("1APP"));
+        assert!(!is_valid_prefix("APP-"));
+    }
+
+    #[test]
+    fn values_are_single_quoted() {
+        assert_eq!(quote_posix("it's"), r"'it'\''s'");
+        assert_eq!(quote_posix(r"a\b $x"), r"'a\b $x'");
+        assert_eq!(quote_posix("a\nb"), "'a\nb'");
+        assert_eq!(quote_fish("it's"), r"'it\'s'");
+        assert_eq!(quote_fish(r"a\b $x"), r"'a\\b $x'");
+        assert_eq!(quote_fish("a\nb"), "'a\nb'");
+    }
+
+    #[test]
+    fn statements_are_written_for_each_shell() {
+        let variables = [EnvironmentVariable {
+            name: String::from("PW"),
+            key: String::from("pw"),
+            value: String::from("it's"),
+        }];
+        assert_eq!(
+            export_statements(Shell::Bash, &variables),
+            "export PW='it'\\''s'\n"
+        );
+        assert_eq!(
+            export_statements(Shell::Fish, &variables),
+            "set -gx PW 'it\\'s'\n"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn posix_statements_set_the_exact_values_when_evaluated() {
+        for value in TRICKY_VALUES {
+            let variables = [EnvironmentVariable {
+                name: String::from("REISBASE_TEST_VALUE"),
+                key: String::from("value"),
+                value: value.to_owned(),
+            }];
+            let script = format!(
+                "{}printf %s \"$REISBASE_TEST_VALUE\"",
+                export_statements(Shell::Bash, &variables)
+            );
+            let output = Command::new("sh")
+                .args(["-c", &script])
+                .output()
+                .expect("sh runs");
+            assert_eq!(String::from_utf8_lossy(&output.stdout), value);
+        }
+    }
+}
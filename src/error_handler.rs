@@ -48,30 +48,38 @@ impl ErrorHandler {
                     error
                 ))
             }
-            // ErrorKind::ReadOnlyFilesystem => {
-            //     CustomReisFailure::PermissionDeniedForDatabase(CustomErrorMessage::new(
-            //         String::from(
-            //             "Your file system is read only, so we can't create our database on it!",
-            //         ),
-            //         error,
-            //     ))
-            // }
-            // ErrorKind::FileTooLarge => {
-            //     CustomReisFailure::DatabaseTooLargeError(CustomErrorMessage::new(
-            //         String::from(
-            //             "The database has a file size larger than what is supported!"
-            //     ),
-            //         error
-            //     ))
-            // }
-            // ErrorKind::InvalidFilename => { 
-            //     CustomReisFailure::InvalidDatabaseNameFailure(CustomErrorMessage::new(
-            //         String::from(
-            //             "The database filename exceeded the filename length limit."
-            //         ),
-            //         error
-            //     ))
-            // },
+            ErrorKind::ReadOnlyFilesystem => {
+                CustomReisIOFailure::PermissionDeniedForDatabase(CustomErrorMessage::new(
+                    String::from(
+                        "Your file system is read only, so we can't create our database on it!",
+                    ),
+                    error,
+                ))
+            }
+            ErrorKind::StorageFull => {
+                CustomReisIOFailure::OutOfSpace(CustomErrorMessage::new(
+                    String::from(
+                        "There is no space left on the device to write the database!"
+                    ),
+                    error
+                ))
+            }
+            ErrorKind::FileTooLarge => {
+                CustomReisIOFailure::DatabaseTooLarge(CustomErrorMessage::new(
+                    String::from(
+                        "The database has a file size larger than what is supported!"
+                    ),
+                    error
+                ))
+            }
+            ErrorKind::InvalidFilename => {
+                CustomReisIOFailure::InvalidDatabaseName(CustomErrorMessage::new(
+                    String::from(
+                        "The database filename exceeded the filename length limit."
+                    ),
+                    error
+                ))
+            },
             _ => CustomReisIOFailure::Default(CustomErrorMessage::new(
                 String::from(
                     "An unexpected error ocurred! Please report this error to Rafinha!"
@@ -80,4 +88,10 @@ impl ErrorHandler {
             )),
         }
     }
+
+    /// Reports a problem that doesn't stop the operation. It is printed to stderr in every
+    /// output format, so the output of the operation can still be parsed.
+    pub fn print_warning(message: &str) {
+        eprintln!("Warning: {}", message);
+    }
 }
@@ -0,0 +1,558 @@
+//! Moving entries in and out of reisbase, in formats other programs read and write.
+//!
+//! `json`, `yaml` and `toml` hold a mapping from each key to its value. When importing,
+//! nested mappings are read as keys joined with dots, values that aren't strings as their
+//! JSON text, and null values are left out. `csv` has a header row and one row per entry,
+//! with the `key` and `value` columns and optionally `description`, `expires_at` and `secret`,
+//! so it is the only format that keeps the metadata of the entries. `env` is a dotenv file
+//! with a `KEY="value"` line per entry, each key named as an environment variable.
+//!
+//! Exports are written with the real values, so the export action refuses to write secret
+//! entries unless they are revealed. Secret entries can only be exported as `csv`, since the
+//! other formats would import them back as plain values.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    entry::{KeyedEntry, ReisbaseEntry},
+    environment,
+    failures::CustomReisIOFailure,
+    time,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExchangeFormat {
+    #[default]
+    Json,
+    Yaml,
+    Toml,
+    Csv,
+    Env,
+}
+
+impl ExchangeFormat {
+    pub fn new(format: &str) -> Option<ExchangeFormat> {
+        match format {
+            "json" => Some(ExchangeFormat::Json),
+            "yaml" | "yml" => Some(ExchangeFormat::Yaml),
+            "toml" => Some(ExchangeFormat::Toml),
+            "csv" => Some(ExchangeFormat::Csv),
+            "env" => Some(ExchangeFormat::Env),
+            _ => None,
+        }
+    }
+
+    /// The format of the file at `path`, told by its extension, or by its name for `.env`
+    /// files.
+    pub fn from_path(path: &str) -> Option<ExchangeFormat> {
+        let file_name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+        if file_name == ".env" {
+            return Some(ExchangeFormat::Env);
+        }
+        file_name
+            .rsplit_once('.')
+            .and_then(|(_, extension)| ExchangeFormat::new(&extension.to_lowercase()))
+    }
+
+    /// Whether entries exported in this format are imported back with their metadata,
+    /// including whether they are secret.
+    pub fn keeps_metadata(&self) -> bool {
+        matches!(self, ExchangeFormat::Csv)
+    }
+
+    /// Writes `entries` in this format, failing if some are secret and the format can't keep
+    /// them so.
+    pub fn export(&self, entries: &[KeyedEntry]) -> Result<String, CustomReisIOFailure> {
+        if !self.keeps_metadata() && entries.iter().any(|keyed| keyed.entry.secret) {
+            return Err(CustomReisIOFailure::secrets_in_lossy_export(
+                &self.to_string(),
+            ));
+        }
+        let values = entries
+            .iter()
+            .map(|keyed| (keyed.key.as_str(), keyed.entry.value.as_str()))
+            .collect::<BTreeMap<&str, &str>>();
+        let exported = match self {
+            ExchangeFormat::Json => serde_json::to_string_pretty(&values)
+                .map(|json| json + "\n")
+                .map_err(|error| error.to_string()),
+            ExchangeFormat::Yaml => {
+                serde_yaml::to_string(&values).map_err(|error| error.to_string())
+            }
+            ExchangeFormat::Toml => toml::to_string(&values).map_err(|error| error.to_string()),
+            ExchangeFormat::Csv => export_csv(entries).map_err(|error| error.to_string()),
+            ExchangeFormat::Env => export_env(entries),
+        };
+        exported.map_err(|error| CustomReisIOFailure::export_failure(&self.to_string(), &error))
+    }
+
+    /// Reads the entries in `contents`. The rows of `csv` and the lines of `env` are read in
+    /// the order they are written, and the keys of the other formats sorted.
+    pub fn import(&self, contents: &str) -> Result<Vec<KeyedEntry>, CustomReisIOFailure> {
+        let imported = match self {
+            ExchangeFormat::Json => serde_json::from_str(contents)
+                .map_err(|error| error.to_string())
+                .and_then(mapping_entries),
+            ExchangeFormat::Yaml => serde_yaml::from_str(contents)
+                .map_err(|error| error.to_string())
+                .and_then(mapping_entries),
+            ExchangeFormat::Toml => contents
+                .parse::<toml::Table>()
+                .map_err(|error| error.to_string())
+                .map(|table| toml_to_json(toml::Value::Table(table)))
+                .and_then(mapping_entries),
+            ExchangeFormat::Csv => import_csv(contents),
+            ExchangeFormat::Env => import_env(contents),
+        };
+        let entries = imported
+            .map_err(|error| CustomReisIOFailure::invalid_import(&self.to_string(), &error))?;
+        match entries.iter().find(|keyed| keyed.key.is_empty()) {
+            Some(_) => Err(CustomReisIOFailure::invalid_import(
+                &self.to_string(),
+                "an entry has an empty key",
+            )),
+            None => Ok(entries),
+        }
+    }
+}
+
+impl std::fmt::Display for ExchangeFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExchangeFormat::Json => write!(f, "json"),
+            ExchangeFormat::Yaml => write!(f, "yaml"),
+            ExchangeFormat::Toml => write!(f, "toml"),
+            ExchangeFormat::Csv => write!(f, "csv"),
+            ExchangeFormat::Env => write!(f, "env"),
+        }
+    }
+}
+
+/// What an import does with the entries whose keys already exist.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConflictStrategy {
+    /// Keeps the existing entries.
+    Skip,
+    Overwrite,
+    /// Imports nothing if any key already exists.
+    Fail,
+    /// Imports the other entries, then asks whether to replace each existing one.
+    #[default]
+    Prompt,
+}
+
+impl ConflictStrategy {
+    pub fn new(strategy: &str) -> Option<ConflictStrategy> {
+        match strategy {
+            "skip" => Some(ConflictStrategy::Skip),
+            "overwrite" => Some(ConflictStrategy::Overwrite),
+            "fail" => Some(ConflictStrategy::Fail),
+            "prompt" => Some(ConflictStrategy::Prompt),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for ConflictStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConflictStrategy::Skip => write!(f, "skip"),
+            ConflictStrategy::Overwrite => write!(f, "overwrite"),
+            ConflictStrategy::Fail => write!(f, "fail"),
+            ConflictStrategy::Prompt => write!(f, "prompt"),
+        }
+    }
+}
+
+/// What importing an entry did, or would do in a dry run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportChangeKind {
+    Added,
+    Overwritten,
+    Skipped,
+    /// The key already exists, and is left for the user to decide.
+    Conflict,
+}
+
+impl std::fmt::Display for ImportChangeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportChangeKind::Added => write!(f, "added"),
+            ImportChangeKind::Overwritten => write!(f, "overwritten"),
+            ImportChangeKind::Skipped => write!(f, "skipped"),
+            ImportChangeKind::Conflict => write!(f, "conflict"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ImportChange {
+    pub key: String,
+    pub value: String,
+    pub change: ImportChangeKind,
+}
+
+/// The columns of an exported CSV file, of which only `key` and `value` are required when
+/// importing one.
+#[derive(Debug, Serialize, Deserialize)]
+struct CsvEntry {
+    key: String,
+    value: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    expires_at: Option<String>,
+    #[serde(default)]
+    secret: Option<String>,
+}
+
+fn export_csv(entries: &[KeyedEntry]) -> Result<String, csv::Error> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for keyed in entries {
+        writer.serialize(CsvEntry {
+            key: keyed.key.clone(),
+            value: keyed.entry.value.clone(),
+            description: keyed.entry.description.clone(),
+            expires_at: keyed
+                .entry
+                .expires_at
+                .map(|expires_at| expires_at.to_string()),
+            secret: Some(keyed.entry.secret.to_string()),
+        })?;
+    }
+    let contents = writer
+        .into_inner()
+        .map_err(|error| csv::Error::from(error.into_error()))?;
+    Ok(String::from_utf8_lossy(&contents).into_owned())
+}
+
+fn import_csv(contents: &str) -> Result<Vec<KeyedEntry>, String> {
+    csv::Reader::from_reader(contents.as_bytes())
+        .deserialize::<CsvEntry>()
+        .map(|row| {
+            let row = row.map_err(|error| error.to_string())?;
+            let expires_at = match row.expires_at.filter(|expires_at| !expires_at.is_empty()) {
+                Some(expires_at) => Some(
+                    expires_at
+                        .parse()
+                        .ok()
+                        .or_else(|| time::parse_datetime(&expires_at))
+                        .ok_or_else(|| format!("invalid expiration {}", expires_at))?,
+                ),
+                None => None,
+            };
+            let mut entry = ReisbaseEntry::new(&row.value);
+            entry.description = row
+                .description
+                .filter(|description| !description.is_empty());
+            entry.expires_at = expires_at;
+            entry.secret = row.secret.is_some_and(|secret| secret == "true");
+            Ok(KeyedEntry {
+                key: row.key,
+                entry,
+            })
+        })
+        .collect()
+}
+
+/// Writes a line per entry, failing if two keys would be written as the same variable, since
+/// only the last one would be read back.
+fn export_env(entries: &[KeyedEntry]) -> Result<String, String> {
+    let mut keys = HashMap::<String, &str>::new();
+    let mut contents = String::new();
+    for keyed in entries {
+        let name = environment::variable_name(&keyed.key, None)
+            .ok_or_else(|| format!("the key {} can't be written as a variable", keyed.key))?;
+        if let Some(existing) = keys.insert(name.clone(), &keyed.key) {
+            return Err(format!(
+                "the keys {} and {} would both be written as {}",
+                existing, keyed.key, name
+            ));
+        }
+        contents.push_str(&format!(
+            "{}=\"{}\"\n",
+            name,
+            escape_env(&keyed.entry.value)
+        ));
+    }
+    Ok(contents)
+}
+
+/// Reads the `KEY=value` lines of a dotenv file, which may start with `export`. Values may be
+/// single quoted, to be read as they are, or double quoted, to read escape sequences.
+/// Comments and empty lines are skipped.
+fn import_env(contents: &str) -> Result<Vec<KeyedEntry>, String> {
+    contents
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(index, line)| {
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("line {} has no value", index + 1))?;
+            let value = value.trim_start();
+            let value = if let Some(quoted) = value.strip_prefix('"') {
+                unescape_env(quoted.strip_suffix('"').unwrap_or(quoted))
+            } else if let Some(quoted) = value.strip_prefix('\'') {
+                quoted.strip_suffix('\'').unwrap_or(quoted).to_owned()
+            } else {
+                let unquoted = value.split_once(" #").map_or(value, |(value, _)| value);
+                unquoted.trim_end().to_owned()
+            };
+            Ok(KeyedEntry {
+                key: key.trim().to_owned(),
+                entry: ReisbaseEntry::new(&value),
+            })
+        })
+        .collect()
+}
+
+fn escape_env(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for character in value.chars() {
+        match character {
+            '\\' | '"' | '$' | '`' => {
+                escaped.push('\\');
+                escaped.push(character);
+            }
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+fn unescape_env(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut characters = value.chars();
+    while let Some(character) = characters.next() {
+        match (character, characters.clone().next()) {
+            ('\\', Some('n')) => {
+                characters.next();
+                unescaped.push('\n');
+            }
+            ('\\', Some(escaped @ ('\\' | '"' | '$' | '`'))) => {
+                characters.next();
+                unescaped.push(escaped);
+            }
+            _ => unescaped.push(character),
+        }
+    }
+    unescaped
+}
+
+/// Reads the entries of a mapping, joining the keys of nested mappings with dots.
+fn mapping_entries(value: serde_json::Value) -> Result<Vec<KeyedEntry>, String> {
+    match value {
+        serde_json::Value::Object(mapping) => {
+            let mut entries = Vec::new();
+            for (key, value) in mapping {
+                flatten_into(key, value, &mut entries);
+            }
+            Ok(entries)
+        }
+        _ => Err(String::from(
+            "the contents are not a mapping of keys to values",
+        )),
+    }
+}
+
+fn flatten_into(key: String, value: serde_json::Value, entries: &mut Vec<KeyedEntry>) {
+    let value = match value {
+        serde_json::Value::Object(mapping) => {
+            for (nested_key, value) in mapping {
+                flatten_into(format!("{}.{}", key, nested_key), value, entries);
+            }
+            return;
+        }
+        serde_json::Value::Null => return,
+        serde_json::Value::String(value) => value,
+        value => value.to_string(),
+    };
+    entries.push(KeyedEntry {
+        key,
+        entry: ReisbaseEntry::new(&value),
+    });
+}
+
+fn toml_to_json(value: toml::Value) -> serde_json::Value {
+    match value {
+        toml::Value::String(value) => serde_json::Value::String(value),
+        toml::Value::Integer(value) => serde_json::Value::from(value),
+        toml::Value::Float(value) => serde_json::Value::from(value),
+        toml::Value::Boolean(value) => serde_json::Value::Bool(value),
+        toml::Value::Datetime(value) => serde_json::Value::String(value.to_string()),
+        toml::Value::Array(values) => values.into_iter().map(toml_to_json).collect(),
+        toml::Value::Table(table) => serde_json::Value::Object(
+            table
+                .into_iter()
+                .map(|(key, value)| (key, toml_to_json(value)))
+                .collect(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keyed(key: &str, value: &str) -> KeyedEntry {
+        KeyedEntry {
+            key: key.to_owned(),
+            entry: ReisbaseEntry::new(value),
+        }
+    }
+
+    fn round_trip(format: ExchangeFormat, entries: &[KeyedEntry]) -> Vec<KeyedEntry> {
+        let exported = format.export(entries).expect("the entries are exported");
+        format.import(&exported).expect("the export is imported")
+    }
+
+    fn import_error(format: ExchangeFormat, contents: &str) -> String {
+        match format.import(contents) {
+            Ok(entries) => panic!("{} was imported as {:?}", contents, entries),
+            Err(error) => error.error_message().message().to_owned(),
+        }
+    }
+
+    #[test]
+    fn mappings_are_imported_back_unchanged() {
+        let entries = vec![
+            keyed("db.host", "localhost"),
+            keyed("empty", ""),
+            keyed("quote", "it's \"quoted\"\n\ttabbed"),
+            keyed("unicode", "é🦀"),
+        ];
+        for format in [
+            ExchangeFormat::Json,
+            ExchangeFormat::Yaml,
+            ExchangeFormat::Toml,
+        ] {
+            assert_eq!(round_trip(format, &entries), entries, "{}", format);
+        }
+    }
+
+    #[test]
+    fn csv_keeps_the_metadata_of_entries() {
+        let entries = vec![
+            KeyedEntry {
+                key: String::from("pw"),
+                entry: ReisbaseEntry {
+                    value: String::from("hunter2, \"quoted\"\nnext"),
+                    description: Some(String::from("the password")),
+                    expires_at: Some(1_700_000_000),
+                    secret: true,
+                },
+            },
+            keyed("host", "localhost"),
+        ];
+        assert_eq!(round_trip(ExchangeFormat::Csv, &entries), entries);
+    }
+
+    #[test]
+    fn env_values_are_escaped_and_read_back() {
+        let entries = vec![
+            keyed("HOST", "localhost"),
+            keyed("QUOTED", "say \"hi\" to $USER `now` \\ back"),
+            keyed("LINES", "first\nsecond"),
+            keyed("EMPTY", ""),
+        ];
+        assert_eq!(round_trip(ExchangeFormat::Env, &entries), entries);
+    }
+
+    #[test]
+    fn env_keys_are_written_as_variables() {
+        let exported = ExchangeFormat::Env
+            .export(&[keyed("db:host", "localhost"), keyed("1st", "one")])
+            .expect("the entries are exported");
+        assert_eq!(exported, "DB_HOST=\"localhost\"\n_1ST=\"one\"\n");
+    }
+
+    #[test]
+    fn env_exports_fail_on_keys_written_as_the_same_variable() {
+        for keys in [["a-b", "a_b"], ["A", "a"]] {
+            let entries = keys.map(|key| keyed(key, "value"));
+            assert!(ExchangeFormat::Env.export(&entries).is_err(), "{:?}", keys);
+        }
+    }
+
+    #[test]
+    fn secrets_are_only_exported_as_csv() {
+        let secret = [KeyedEntry {
+            key: String::from("pw"),
+            entry: ReisbaseEntry {
+                secret: true,
+                ..ReisbaseEntry::new("hunter2")
+            },
+        }];
+        for format in [
+            ExchangeFormat::Json,
+            ExchangeFormat::Yaml,
+            ExchangeFormat::Toml,
+            ExchangeFormat::Env,
+        ] {
+            assert!(format.export(&secret).is_err(), "{}", format);
+        }
+        assert!(ExchangeFormat::Csv.export(&secret).is_ok());
+    }
+
+    #[test]
+    fn env_files_are_read_like_dotenv() {
+        let contents =
+            "# comment\n\nexport A=plain # trailing\nB='single $quoted'\nC=\"double\\n\"\n";
+        assert_eq!(
+            ExchangeFormat::Env
+                .import(contents)
+                .expect("the file is read"),
+            vec![
+                keyed("A", "plain"),
+                keyed("B", "single $quoted"),
+                keyed("C", "double\n"),
+            ]
+        );
+    }
+
+    #[test]
+    fn nested_mappings_are_joined_with_dots() {
+        let contents = r#"{"db": {"host": "localhost", "port": 5432}, "gone": null}"#;
+        assert_eq!(
+            ExchangeFormat::Json
+                .import(contents)
+                .expect("the file is read"),
+            vec![keyed("db.host", "localhost"), keyed("db.port", "5432")]
+        );
+    }
+
+    #[test]
+    fn malformed_files_are_refused() {
+        let malformed = [
+            (ExchangeFormat::Json, "{\"unterminated\": "),
+            (ExchangeFormat::Json, "[\"not\", \"a mapping\"]"),
+            (ExchangeFormat::Json, "{\"\": \"empty key\"}"),
+            (ExchangeFormat::Yaml, "key: [unclosed"),
+            (ExchangeFormat::Yaml, "- a list"),
+            (ExchangeFormat::Toml, "key = "),
+            (ExchangeFormat::Csv, "value\nno key column\n"),
+            (
+                ExchangeFormat::Csv,
+                "key,value,expires_at\nhost,x,someday\n",
+            ),
+            (ExchangeFormat::Env, "NO_VALUE\n"),
+            (ExchangeFormat::Env, "=no key\n"),
+        ];
+        for (format, contents) in malformed {
+            assert!(
+                import_error(format, contents).contains(&format.to_string()),
+                "{}: {}",
+                format,
+                contents
+            );
+        }
+    }
+}
@@ -1,26 +1,47 @@
-use std::io::Error;
+use std::{io::Error, path::Path};
 
-use crate::{actions::ReisbaseAction, arguments::ReisbaseActionsArguments};
+use serde::{ser::SerializeStruct, Serialize, Serializer};
+
+use crate::{
+    actions::ReisbaseAction,
+    arguments::ReisbaseActionsArguments,
+    constants::{
+        the_action_requires_arguments, the_database_already_exists, the_entry_does_not_exists,
+        the_history_version_does_not_exists, the_import_failed_on_conflicts,
+        the_import_has_conflicts, the_key_already_exists, the_key_has_no_history,
+        the_namespace_already_exists, the_namespace_does_not_exists, the_secrets_are_not_revealed,
+        the_setting_is_not_set, the_template_keys_are_missing, the_variable_name_is_invalid,
+        the_variables_conflict, DatabaseStringConstants, ExitCodes, ALREADY_ENCRYPTED,
+        EMPTY_DATABASE, NOTHING_TO_UNDO, NOT_ENCRYPTED, NO_MATCHING_ENTRIES, NO_NAMESPACES,
+        NO_SETTINGS,
+    },
+    database_format::CorruptLine,
+    entry::{ReisbaseEntry, SensitiveValue},
+};
 
 #[derive(Debug)]
 pub enum CustomReisIOFailure {
+    CommandFailure(CustomErrorMessage),
     CorruptedDatabase(CustomErrorMessage),
     DatabaseNotFound(CustomErrorMessage),
     DatabaseTooLarge(CustomErrorMessage),
     Default(CustomErrorMessage),
     InvalidActionArguments(CustomErrorMessage),
+    InvalidConfig(CustomErrorMessage),
     InvalidDatabaseName(CustomErrorMessage),
     InvalidInput(CustomErrorMessage),
     InvalidPlatformOperation(CustomErrorMessage),
     PermissionDeniedForDatabase(CustomErrorMessage),
     OutOfSpace(CustomErrorMessage),
     UnknownActionRequest(CustomErrorMessage),
+    WrongPassphrase(CustomErrorMessage),
 }
 
 impl CustomReisIOFailure {
     pub fn error_message(&self) -> &CustomErrorMessage {
         match self {
-            CustomReisIOFailure::CorruptedDatabase(error_message)
+            CustomReisIOFailure::CommandFailure(error_message)
+            | CustomReisIOFailure::CorruptedDatabase(error_message)
             | CustomReisIOFailure::DatabaseNotFound(error_message)
             | CustomReisIOFailure::DatabaseTooLarge(error_message)
             | CustomReisIOFailure::Default(error_message)
@@ -30,7 +51,53 @@ impl CustomReisIOFailure {
             | CustomReisIOFailure::PermissionDeniedForDatabase(error_message)
             | CustomReisIOFailure::OutOfSpace(error_message)
             | CustomReisIOFailure::InvalidActionArguments(error_message)
-            | CustomReisIOFailure::UnknownActionRequest(error_message) => error_message,
+            | CustomReisIOFailure::InvalidConfig(error_message)
+            | CustomReisIOFailure::UnknownActionRequest(error_message)
+            | CustomReisIOFailure::WrongPassphrase(error_message) => error_message,
+        }
+    }
+
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            CustomReisIOFailure::CommandFailure(error_message)
+                if error_message.error().kind() == std::io::ErrorKind::NotFound =>
+            {
+                ExitCodes::COMMAND_NOT_FOUND
+            }
+            CustomReisIOFailure::CommandFailure(_) => ExitCodes::COMMAND_NOT_EXECUTABLE,
+            CustomReisIOFailure::CorruptedDatabase(_) => ExitCodes::CORRUPT_DATABASE,
+            CustomReisIOFailure::Default(_) => ExitCodes::FAILURE,
+            CustomReisIOFailure::InvalidActionArguments(_)
+            | CustomReisIOFailure::InvalidConfig(_)
+            | CustomReisIOFailure::InvalidInput(_) => ExitCodes::INVALID_ARGUMENTS,
+            CustomReisIOFailure::UnknownActionRequest(_) => ExitCodes::UNKNOWN_ACTION,
+            CustomReisIOFailure::DatabaseNotFound(_)
+            | CustomReisIOFailure::DatabaseTooLarge(_)
+            | CustomReisIOFailure::InvalidDatabaseName(_)
+            | CustomReisIOFailure::InvalidPlatformOperation(_)
+            | CustomReisIOFailure::PermissionDeniedForDatabase(_)
+            | CustomReisIOFailure::OutOfSpace(_) => ExitCodes::IO_FAILURE,
+            CustomReisIOFailure::WrongPassphrase(_) => ExitCodes::WRONG_PASSPHRASE,
+        }
+    }
+
+    /// The name identifying this failure in structured output.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            CustomReisIOFailure::CommandFailure(_) => "command_failure",
+            CustomReisIOFailure::CorruptedDatabase(_) => "corrupted_database",
+            CustomReisIOFailure::DatabaseNotFound(_) => "database_not_found",
+            CustomReisIOFailure::DatabaseTooLarge(_) => "database_too_large",
+            CustomReisIOFailure::Default(_) => "default",
+            CustomReisIOFailure::InvalidActionArguments(_) => "invalid_action_arguments",
+            CustomReisIOFailure::InvalidConfig(_) => "invalid_config",
+            CustomReisIOFailure::InvalidDatabaseName(_) => "invalid_database_name",
+            CustomReisIOFailure::InvalidInput(_) => "invalid_input",
+            CustomReisIOFailure::InvalidPlatformOperation(_) => "invalid_platform_operation",
+            CustomReisIOFailure::PermissionDeniedForDatabase(_) => "permission_denied_for_database",
+            CustomReisIOFailure::OutOfSpace(_) => "out_of_space",
+            CustomReisIOFailure::UnknownActionRequest(_) => "unknown_action_request",
+            CustomReisIOFailure::WrongPassphrase(_) => "wrong_passphrase",
         }
     }
 
@@ -45,11 +112,310 @@ impl CustomReisIOFailure {
         })
     }
 
-    pub fn unknown_action_requested(action: &str) -> CustomReisIOFailure {
+    pub fn missing_argument_value(argument: &str) -> CustomReisIOFailure {
+        CustomReisIOFailure::InvalidInput(CustomErrorMessage {
+            message: format!("The argument {} requires a value!", argument),
+            error: Error::new(std::io::ErrorKind::InvalidInput, argument),
+        })
+    }
+
+    pub fn invalid_argument_value(argument: &str, value: &str) -> CustomReisIOFailure {
+        CustomReisIOFailure::InvalidInput(CustomErrorMessage {
+            message: format!(
+                "The value {} is not valid for the argument {}!",
+                value, argument
+            ),
+            error: Error::new(std::io::ErrorKind::InvalidInput, value.to_owned()),
+        })
+    }
+
+    pub fn secrets_in_lossy_export(format: &str) -> CustomReisIOFailure {
+        CustomReisIOFailure::InvalidInput(CustomErrorMessage {
+            message: format!(
+                "The {} format can't keep entries secret! You can export them as csv, or leave them out by picking the entries with --ns.",
+                format
+            ),
+            error: Error::new(std::io::ErrorKind::InvalidInput, format.to_owned()),
+        })
+    }
+
+    pub fn invalid_import(format: &str, error: &str) -> CustomReisIOFailure {
+        CustomReisIOFailure::InvalidInput(CustomErrorMessage {
+            message: format!("The entries couldn't be read as {}!", format),
+            error: Error::new(std::io::ErrorKind::InvalidData, error.to_owned()),
+        })
+    }
+
+    pub fn export_failure(format: &str, error: &str) -> CustomReisIOFailure {
+        CustomReisIOFailure::Default(CustomErrorMessage {
+            message: format!("The entries couldn't be written as {}!", format),
+            error: Error::new(std::io::ErrorKind::InvalidData, error.to_owned()),
+        })
+    }
+
+    pub fn missing_command() -> CustomReisIOFailure {
+        CustomReisIOFailure::InvalidActionArguments(CustomErrorMessage {
+            message: String::from(
+                "The Run action requires the command to run after --, such as reis run -- env!",
+            ),
+            error: Error::new(std::io::ErrorKind::InvalidInput, "See message"),
+        })
+    }
+
+    pub fn unknown_config_verb(verb: &str) -> CustomReisIOFailure {
+        CustomReisIOFailure::InvalidActionArguments(CustomErrorMessage {
+            message: format!(
+                "The Config action has no {} subcommand! Use config list, config get or config set.",
+                verb
+            ),
+            error: Error::new(std::io::ErrorKind::InvalidInput, verb.to_owned()),
+        })
+    }
+
+    pub fn command_failure(command: &str, error: Error) -> CustomReisIOFailure {
+        CustomReisIOFailure::CommandFailure(CustomErrorMessage {
+            message: format!("The command {} couldn't be run!", command),
+            error,
+        })
+    }
+
+    pub fn invalid_template(source: &str, error: &str) -> CustomReisIOFailure {
+        CustomReisIOFailure::InvalidInput(CustomErrorMessage {
+            message: format!("The template {} couldn't be read!", source),
+            error: Error::new(std::io::ErrorKind::InvalidData, error.to_owned()),
+        })
+    }
+
+    pub fn unknown_setting(setting: &str) -> CustomReisIOFailure {
+        CustomReisIOFailure::InvalidInput(CustomErrorMessage {
+            message: format!(
+                "The setting {} doesn't exist! You can list the configured ones with the config action.",
+                setting
+            ),
+            error: Error::new(std::io::ErrorKind::InvalidInput, setting.to_owned()),
+        })
+    }
+
+    pub fn invalid_setting_value(setting: &str, value: &str) -> CustomReisIOFailure {
+        CustomReisIOFailure::InvalidInput(CustomErrorMessage {
+            message: format!(
+                "The value {} is not valid for the setting {}!",
+                value, setting
+            ),
+            error: Error::new(std::io::ErrorKind::InvalidInput, value.to_owned()),
+        })
+    }
+
+    pub fn user_only_setting(setting: &str) -> CustomReisIOFailure {
+        CustomReisIOFailure::InvalidInput(CustomErrorMessage {
+            message: format!(
+                "The setting {} can only be set in the user configuration, not in a project!",
+                setting
+            ),
+            error: Error::new(std::io::ErrorKind::InvalidInput, setting.to_owned()),
+        })
+    }
+
+    pub fn invalid_config(path: &Path, error: toml::de::Error) -> CustomReisIOFailure {
+        CustomReisIOFailure::InvalidConfig(CustomErrorMessage {
+            message: format!(
+                "The configuration file {} couldn't be read!",
+                path.display()
+            ),
+            error: Error::new(std::io::ErrorKind::InvalidData, error.message().to_owned()),
+        })
+    }
+
+    pub fn invalid_config_setting(path: &Path, setting: &str) -> CustomReisIOFailure {
+        CustomReisIOFailure::InvalidConfig(CustomErrorMessage {
+            message: format!(
+                "The setting {} in the configuration file {} is unknown or has an invalid value!",
+                setting,
+                path.display()
+            ),
+            error: Error::new(std::io::ErrorKind::InvalidData, setting.to_owned()),
+        })
+    }
+
+    pub fn config_unavailable() -> CustomReisIOFailure {
+        CustomReisIOFailure::InvalidConfig(CustomErrorMessage {
+            message: String::from(
+                "The user configuration couldn't be found, since neither XDG_CONFIG_HOME nor HOME are set!",
+            ),
+            error: Error::new(std::io::ErrorKind::NotFound, "XDG_CONFIG_HOME"),
+        })
+    }
+
+    pub fn conflicting_value_sources(action_name: &str) -> CustomReisIOFailure {
+        CustomReisIOFailure::InvalidActionArguments(CustomErrorMessage {
+            message: format!(
+                "The value for the {} action can only be read from one place!",
+                action_name
+            ),
+            error: Error::new(std::io::ErrorKind::InvalidInput, "See message"),
+        })
+    }
+
+    pub fn clipboard_unavailable(failures: &[String]) -> CustomReisIOFailure {
+        CustomReisIOFailure::InvalidPlatformOperation(CustomErrorMessage {
+            message: String::from(
+                "No clipboard could be reached! You can choose how to reach it with --clipboard.",
+            ),
+            error: Error::new(std::io::ErrorKind::Unsupported, failures.join("; ")),
+        })
+    }
+
+    pub fn clipboard_clear_unavailable(backend: &str) -> CustomReisIOFailure {
+        CustomReisIOFailure::InvalidPlatformOperation(CustomErrorMessage {
+            message: format!(
+                "The value was copied, but it can't be cleared from the clipboard, since {} can't read it back! You can choose another clipboard with --clipboard, or keep the value copied with --clear-after 0.",
+                backend
+            ),
+            error: Error::new(
+                std::io::ErrorKind::Unsupported,
+                format!("the clipboard can't be read through {}", backend),
+            ),
+        })
+    }
+
+    pub fn clipboard_helper_failure(error: Error) -> CustomReisIOFailure {
+        CustomReisIOFailure::InvalidPlatformOperation(CustomErrorMessage {
+            message: String::from(
+                "The value was copied, but it couldn't be scheduled to be cleared from the clipboard!",
+            ),
+            error,
+        })
+    }
+
+    pub fn value_read_failure(source: &str, error: Error) -> CustomReisIOFailure {
+        CustomReisIOFailure::InvalidInput(CustomErrorMessage {
+            message: format!("The value could not be read from {}!", source),
+            error,
+        })
+    }
+
+    pub fn invalid_search_pattern(pattern: &str, error: regex::Error) -> CustomReisIOFailure {
+        CustomReisIOFailure::InvalidInput(CustomErrorMessage {
+            message: format!("The search pattern {} is not valid!", pattern),
+            error: Error::new(std::io::ErrorKind::InvalidInput, error),
+        })
+    }
+
+    pub fn corrupted_database(db_name: &str, corrupt_lines: &[CorruptLine]) -> CustomReisIOFailure {
+        let lines = corrupt_lines
+            .iter()
+            .map(|line| format!("\n  {}", line))
+            .collect::<String>();
+        CustomReisIOFailure::CorruptedDatabase(CustomErrorMessage {
+            message: format!(
+                "The database {} contains lines that can't be read, so it won't be changed until it is repaired with the repair action:{}",
+                db_name, lines
+            ),
+            error: Error::new(std::io::ErrorKind::InvalidData, db_name),
+        })
+    }
+
+    pub fn wrong_passphrase(db_name: &str) -> CustomReisIOFailure {
+        CustomReisIOFailure::WrongPassphrase(CustomErrorMessage {
+            message: format!("The passphrase for the database {} is wrong!", db_name),
+            error: Error::new(std::io::ErrorKind::PermissionDenied, db_name),
+        })
+    }
+
+    pub fn corrupted_encrypted_database(db_name: &str) -> CustomReisIOFailure {
+        CustomReisIOFailure::CorruptedDatabase(CustomErrorMessage {
+            message: format!(
+                "The encrypted database {} is damaged, and can't be decrypted! You can restore its backup with the restore-backup action.",
+                db_name
+            ),
+            error: Error::new(std::io::ErrorKind::InvalidData, db_name),
+        })
+    }
+
+    pub fn passphrase_unavailable(error: Error) -> CustomReisIOFailure {
+        CustomReisIOFailure::InvalidInput(CustomErrorMessage {
+            message: String::from(
+                "The passphrase could not be read! You can also set it in the REISBASE_PASSPHRASE environment variable.",
+            ),
+            error,
+        })
+    }
+
+    pub fn passphrases_dont_match() -> CustomReisIOFailure {
+        CustomReisIOFailure::InvalidInput(CustomErrorMessage {
+            message: String::from("The passphrases are empty or don't match!"),
+            error: Error::new(std::io::ErrorKind::InvalidInput, "See message"),
+        })
+    }
+
+    pub fn encryption_failure(reason: &str) -> CustomReisIOFailure {
+        CustomReisIOFailure::Default(CustomErrorMessage {
+            message: String::from("The database could not be encrypted or decrypted!"),
+            error: Error::other(reason.to_owned()),
+        })
+    }
+
+    pub fn backup_not_found(backup_path: &str, error: Error) -> CustomReisIOFailure {
+        CustomReisIOFailure::DatabaseNotFound(CustomErrorMessage {
+            message: format!(
+                "The backup {} could not be read! A backup is only created after the database is changed.",
+                backup_path
+            ),
+            error,
+        })
+    }
+
+    pub fn unsupported_database_version(db_name: &str, version: &str) -> CustomReisIOFailure {
+        CustomReisIOFailure::CorruptedDatabase(CustomErrorMessage {
+            message: format!(
+                "The database {} is written in version {} of the format, which this version of reisbase can't read! Update reisbase to use it.",
+                db_name, version
+            ),
+            error: Error::new(std::io::ErrorKind::InvalidData, version.to_owned()),
+        })
+    }
+
+    pub fn local_database_not_found() -> CustomReisIOFailure {
+        CustomReisIOFailure::DatabaseNotFound(CustomErrorMessage {
+            message: format!(
+                "No {} database was found in this directory or its parents! You can create one with the init action.",
+                DatabaseStringConstants::LOCAL_DATABASE_NAME
+            ),
+            error: Error::new(
+                std::io::ErrorKind::NotFound,
+                DatabaseStringConstants::LOCAL_DATABASE_NAME,
+            ),
+        })
+    }
+
+    pub fn global_database_unavailable() -> CustomReisIOFailure {
+        CustomReisIOFailure::DatabaseNotFound(CustomErrorMessage {
+            message: String::from(
+                "The global database could not be found, since neither XDG_DATA_HOME nor HOME are set!",
+            ),
+            error: Error::new(std::io::ErrorKind::NotFound, "XDG_DATA_HOME"),
+        })
+    }
+
+    pub fn current_directory_unavailable(error: Error) -> CustomReisIOFailure {
+        CustomReisIOFailure::DatabaseNotFound(CustomErrorMessage {
+            message: String::from(
+                "The current directory could not be read, so the database could not be found!",
+            ),
+            error,
+        })
+    }
+
+    pub fn unknown_action_requested(action: &str, suggestions: &[&str]) -> CustomReisIOFailure {
+        let suggestions = if suggestions.is_empty() {
+            String::new()
+        } else {
+            format!(" Did you mean {}?", suggestions.join(", "))
+        };
         CustomReisIOFailure::UnknownActionRequest(CustomErrorMessage {
             message: format!(
-                "The argument {} is not recongnized as a real action.",
-                action
+                "The argument {} is not recongnized as a real action.{}",
+                action, suggestions
             ),
             error: Error::new(std::io::ErrorKind::InvalidInput, action),
         })
@@ -83,46 +449,327 @@ impl CustomErrorMessage {
     }
 }
 
-#[derive(Debug)]
+/// An action that couldn't be completed as requested, but may be retried. Each variant is
+/// serialized as the `data` of a report, without the fields only needed for retrying.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
 pub enum CustomReisActionWarning {
     EmptyDatabase,
+    NoMatchingEntries,
+    NothingToUndo,
+    AlreadyEncrypted,
+    NotEncrypted,
+    NoNamespaces,
+    NoSettings,
+    SettingNotSet {
+        setting: String,
+    },
+    DatabaseAlreadyExists {
+        path: String,
+    },
+    NamespaceAlreadyExists {
+        namespace: String,
+    },
+    NamespaceDoesntExists {
+        namespace: String,
+    },
     EntryAlreadyExists {
         key: String,
-        old_value: String,
-        new_value: String,
+        old_value: SensitiveValue,
+        new_value: SensitiveValue,
+        #[serde(skip)]
+        arguments: Vec<ReisbaseActionsArguments>,
     },
     EntryDoesntExists {
         key: String,
-        value: Option<String>,
+        value: Option<SensitiveValue>,
+        /// Name of the action that looked the key up, so it can be retried with another key.
+        action: &'static str,
+        #[serde(skip)]
+        arguments: Vec<ReisbaseActionsArguments>,
+        /// Existing keys `key` is likely a typo of, from the closest.
+        suggestions: Vec<String>,
+    },
+    ImportConflicts {
+        source: String,
+        /// How many entries were imported before asking about the conflicting ones.
+        imported: usize,
+        /// An [`CustomReisActionWarning::EntryAlreadyExists`] for each key that already
+        /// exists, so each one can be replaced after a confirmation.
+        conflicts: Vec<CustomReisActionWarning>,
+        /// Whether the conflicting entries should be confirmed one by one, instead of
+        /// failing the whole import.
+        #[serde(skip)]
+        prompt: bool,
+    },
+    ConflictingVariables {
+        variable: String,
+        /// The keys that would both be exported as `variable`.
+        keys: Vec<String>,
+    },
+    InvalidVariableName {
+        key: String,
+    },
+    MissingTemplateKeys {
+        source: String,
+        /// The keys of the template that don't exist, in the order they first appear.
+        keys: Vec<String>,
+    },
+    SecretsNotRevealed {
+        /// The secret keys whose values would have been written, in the order they were found.
+        keys: Vec<String>,
+    },
+    NoHistory {
+        key: String,
+    },
+    HistoryVersionDoesntExists {
+        key: String,
+        version: usize,
     },
     RequiredArgumentsNotSpecified {
-        operation: ReisbaseAction,
+        /// The action along with the arguments it requires.
+        #[serde(serialize_with = "serialize_required_arguments")]
+        operation: Box<ReisbaseAction>,
+        /// The key and arguments the action was requested with, so it can be retried with the
+        /// required arguments added.
+        #[serde(skip)]
+        key: Option<String>,
+        #[serde(skip)]
+        arguments: Vec<ReisbaseActionsArguments>,
     },
 }
 
+/// Serializes an action by its name and the arguments it requires.
+fn serialize_required_arguments<S: Serializer>(
+    operation: &ReisbaseAction,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut state = serializer.serialize_struct("ReisbaseAction", 2)?;
+    state.serialize_field("action", operation.full_name())?;
+    state.serialize_field("arguments", &required_arguments(operation))?;
+    state.end()
+}
+
+fn required_arguments(operation: &ReisbaseAction) -> Vec<String> {
+    operation
+        .arguments()
+        .iter()
+        .flat_map(ReisbaseActionsArguments::to_args)
+        .collect()
+}
+
 impl CustomReisActionWarning {
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            CustomReisActionWarning::EmptyDatabase
+            | CustomReisActionWarning::NoMatchingEntries
+            | CustomReisActionWarning::NothingToUndo
+            | CustomReisActionWarning::NoNamespaces
+            | CustomReisActionWarning::NoSettings => ExitCodes::EMPTY_DATABASE,
+            CustomReisActionWarning::AlreadyEncrypted
+            | CustomReisActionWarning::NotEncrypted
+            | CustomReisActionWarning::SecretsNotRevealed { .. }
+            | CustomReisActionWarning::InvalidVariableName { .. } => ExitCodes::INVALID_ARGUMENTS,
+            CustomReisActionWarning::EntryAlreadyExists { .. }
+            | CustomReisActionWarning::NamespaceAlreadyExists { .. }
+            | CustomReisActionWarning::DatabaseAlreadyExists { .. }
+            | CustomReisActionWarning::ImportConflicts { .. }
+            | CustomReisActionWarning::ConflictingVariables { .. } => ExitCodes::ALREADY_EXISTS,
+            CustomReisActionWarning::EntryDoesntExists { .. }
+            | CustomReisActionWarning::NamespaceDoesntExists { .. }
+            | CustomReisActionWarning::SettingNotSet { .. }
+            | CustomReisActionWarning::MissingTemplateKeys { .. }
+            | CustomReisActionWarning::NoHistory { .. }
+            | CustomReisActionWarning::HistoryVersionDoesntExists { .. } => {
+                ExitCodes::KEY_NOT_FOUND
+            }
+            CustomReisActionWarning::RequiredArgumentsNotSpecified { .. } => {
+                ExitCodes::INVALID_ARGUMENTS
+            }
+        }
+    }
+
+    /// The name identifying this warning in structured output.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            CustomReisActionWarning::EmptyDatabase => "empty_database",
+            CustomReisActionWarning::NoMatchingEntries => "no_matching_entries",
+            CustomReisActionWarning::NothingToUndo => "nothing_to_undo",
+            CustomReisActionWarning::AlreadyEncrypted => "already_encrypted",
+            CustomReisActionWarning::NotEncrypted => "not_encrypted",
+            CustomReisActionWarning::NoNamespaces => "no_namespaces",
+            CustomReisActionWarning::NoSettings => "no_settings",
+            CustomReisActionWarning::SettingNotSet { .. } => "setting_not_set",
+            CustomReisActionWarning::DatabaseAlreadyExists { .. } => "database_already_exists",
+            CustomReisActionWarning::NamespaceAlreadyExists { .. } => "namespace_already_exists",
+            CustomReisActionWarning::NamespaceDoesntExists { .. } => "namespace_doesnt_exist",
+            CustomReisActionWarning::EntryAlreadyExists { .. } => "entry_already_exists",
+            CustomReisActionWarning::EntryDoesntExists { .. } => "entry_doesnt_exist",
+            CustomReisActionWarning::ImportConflicts { .. } => "import_conflicts",
+            CustomReisActionWarning::ConflictingVariables { .. } => "conflicting_variables",
+            CustomReisActionWarning::InvalidVariableName { .. } => "invalid_variable_name",
+            CustomReisActionWarning::MissingTemplateKeys { .. } => "missing_template_keys",
+            CustomReisActionWarning::SecretsNotRevealed { .. } => "secrets_not_revealed",
+            CustomReisActionWarning::NoHistory { .. } => "no_history",
+            CustomReisActionWarning::HistoryVersionDoesntExists { .. } => {
+                "history_version_doesnt_exist"
+            }
+            CustomReisActionWarning::RequiredArgumentsNotSpecified { .. } => {
+                "required_arguments_not_specified"
+            }
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            CustomReisActionWarning::EmptyDatabase => String::from(EMPTY_DATABASE),
+            CustomReisActionWarning::NoMatchingEntries => String::from(NO_MATCHING_ENTRIES),
+            CustomReisActionWarning::NothingToUndo => String::from(NOTHING_TO_UNDO),
+            CustomReisActionWarning::AlreadyEncrypted => String::from(ALREADY_ENCRYPTED),
+            CustomReisActionWarning::NotEncrypted => String::from(NOT_ENCRYPTED),
+            CustomReisActionWarning::NoNamespaces => String::from(NO_NAMESPACES),
+            CustomReisActionWarning::NoSettings => String::from(NO_SETTINGS),
+            CustomReisActionWarning::SettingNotSet { setting } => the_setting_is_not_set(setting),
+            CustomReisActionWarning::DatabaseAlreadyExists { path } => {
+                the_database_already_exists(path)
+            }
+            CustomReisActionWarning::NamespaceAlreadyExists { namespace } => {
+                the_namespace_already_exists(namespace)
+            }
+            CustomReisActionWarning::NamespaceDoesntExists { namespace } => {
+                the_namespace_does_not_exists(namespace)
+            }
+            CustomReisActionWarning::EntryAlreadyExists { key, old_value, .. } => {
+                the_key_already_exists(key, &old_value.to_string())
+            }
+            CustomReisActionWarning::EntryDoesntExists { key, value, .. } => {
+                let value = value.as_ref().map(SensitiveValue::to_string);
+                the_entry_does_not_exists(key, value.as_deref().unwrap_or("value"))
+            }
+            CustomReisActionWarning::ImportConflicts {
+                source,
+                imported,
+                conflicts,
+                prompt,
+            } => {
+                let keys = conflicts
+                    .iter()
+                    .filter_map(|conflict| match conflict {
+                        CustomReisActionWarning::EntryAlreadyExists { key, .. } => {
+                            Some(key.as_str())
+                        }
+                        _ => None,
+                    })
+                    .collect::<Vec<&str>>();
+                if *prompt {
+                    the_import_has_conflicts(source, *imported, &keys)
+                } else {
+                    the_import_failed_on_conflicts(source, &keys)
+                }
+            }
+            CustomReisActionWarning::ConflictingVariables { variable, keys } => {
+                the_variables_conflict(variable, keys)
+            }
+            CustomReisActionWarning::InvalidVariableName { key } => {
+                the_variable_name_is_invalid(key)
+            }
+            CustomReisActionWarning::MissingTemplateKeys { source, keys } => {
+                the_template_keys_are_missing(source, keys)
+            }
+            CustomReisActionWarning::SecretsNotRevealed { keys } => {
+                the_secrets_are_not_revealed(keys)
+            }
+            CustomReisActionWarning::NoHistory { key } => the_key_has_no_history(key),
+            CustomReisActionWarning::HistoryVersionDoesntExists { key, version } => {
+                the_history_version_does_not_exists(key, *version)
+            }
+            CustomReisActionWarning::RequiredArgumentsNotSpecified { operation, .. } => {
+                the_action_requires_arguments(
+                    operation.action_name(),
+                    &required_arguments(operation),
+                )
+            }
+        }
+    }
+
     pub fn entry_already_exists(
         key: &str,
-        old_value: &str,
+        old_entry: &ReisbaseEntry,
         new_value: &str,
+        arguments: &[ReisbaseActionsArguments],
     ) -> CustomReisActionWarning {
         CustomReisActionWarning::EntryAlreadyExists {
             key: String::from(key),
-            old_value: String::from(old_value),
-            new_value: String::from(new_value),
+            old_value: SensitiveValue::new(&old_entry.value, old_entry.secret),
+            new_value: SensitiveValue::new(
+                new_value,
+                old_entry.secret || arguments.contains(&ReisbaseActionsArguments::Secret),
+            ),
+            arguments: arguments.to_vec(),
         }
     }
-    pub fn entry_doesnt_exists(key: &str, value: Option<&str>) -> CustomReisActionWarning {
+    pub fn entry_doesnt_exists(
+        action: &ReisbaseAction,
+        key: &str,
+        value: Option<&str>,
+        suggestions: Vec<String>,
+    ) -> CustomReisActionWarning {
         Self::EntryDoesntExists {
             key: String::from(key),
-            value: value.map(String::from),
+            value: value.map(|value| {
+                SensitiveValue::new(
+                    value,
+                    action
+                        .arguments()
+                        .contains(&ReisbaseActionsArguments::Secret),
+                )
+            }),
+            action: action.full_name(),
+            arguments: action.arguments().to_vec(),
+            suggestions,
         }
     }
-    pub fn clear_without_force() -> CustomReisActionWarning {
+    pub fn no_history(key: &str) -> CustomReisActionWarning {
+        Self::NoHistory {
+            key: String::from(key),
+        }
+    }
+    pub fn history_version_doesnt_exists(key: &str, version: usize) -> CustomReisActionWarning {
+        Self::HistoryVersionDoesntExists {
+            key: String::from(key),
+            version,
+        }
+    }
+    pub fn clear_without_force(arguments: &[ReisbaseActionsArguments]) -> CustomReisActionWarning {
         Self::RequiredArgumentsNotSpecified {
-            operation: ReisbaseAction::Clear {
+            operation: Box::new(ReisbaseAction::Clear {
                 arguments: vec![ReisbaseActionsArguments::Force],
-            },
+            }),
+            key: None,
+            arguments: arguments.to_vec(),
+        }
+    }
+    pub fn drop_namespace_without_force(
+        namespace: &str,
+        arguments: &[ReisbaseActionsArguments],
+    ) -> CustomReisActionWarning {
+        Self::RequiredArgumentsNotSpecified {
+            operation: Box::new(ReisbaseAction::DropNamespace {
+                namespace: String::from(namespace),
+                arguments: vec![ReisbaseActionsArguments::Force],
+            }),
+            key: Some(String::from(namespace)),
+            arguments: arguments.to_vec(),
+        }
+    }
+    pub fn namespace_already_exists(namespace: &str) -> CustomReisActionWarning {
+        Self::NamespaceAlreadyExists {
+            namespace: String::from(namespace),
+        }
+    }
+    pub fn namespace_doesnt_exists(namespace: &str) -> CustomReisActionWarning {
+        Self::NamespaceDoesntExists {
+            namespace: String::from(namespace),
         }
     }
 }
@@ -132,3 +779,26 @@ pub enum CustomFailureOperation {
     Error(CustomReisIOFailure),
     Warning(CustomReisActionWarning),
 }
+
+impl CustomFailureOperation {
+    /// The status reisbase exits with when the operation fails this way, as documented in
+    /// [`ExitCodes`].
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            CustomFailureOperation::Error(error) => error.exit_code(),
+            CustomFailureOperation::Warning(warning) => warning.exit_code(),
+        }
+    }
+}
+
+impl From<CustomReisIOFailure> for CustomFailureOperation {
+    fn from(error: CustomReisIOFailure) -> Self {
+        CustomFailureOperation::Error(error)
+    }
+}
+
+impl From<CustomReisActionWarning> for CustomFailureOperation {
+    fn from(warning: CustomReisActionWarning) -> Self {
+        CustomFailureOperation::Warning(warning)
+    }
+}
@@ -0,0 +1,233 @@
+use std::{collections::BTreeMap, env};
+
+use crate::{
+    clipboard::ClipboardSelection,
+    config::Configuration,
+    constants::{ClipboardConstants, DatabaseStringConstants, GlobalOptionsConstants},
+    error_handler::ErrorHandler,
+    failures::CustomReisIOFailure,
+    output::OutputFormat,
+    time,
+};
+
+/// Options that apply to the whole invocation, regardless of the requested action.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReisbaseGlobalOptions {
+    /// The database passed with `--db` or set in `REISBASE_DB`, which is used instead of
+    /// looking for one. `REISBASE_DB` is left out when `--local` or `--global` is passed.
+    pub database_path: Option<String>,
+    /// The database set in the configuration, which is used when no database nor store is
+    /// requested.
+    pub configured_database: Option<String>,
+    pub store: StoreSelection,
+    pub output: OutputFormat,
+    pub confirmation: Confirmation,
+    pub clipboard: ClipboardSelection,
+    /// Seconds after which copied values are removed from the clipboard, if they should be.
+    pub clear_after: Option<u64>,
+    /// Seconds after which copied secret values are removed from the clipboard, when
+    /// `clear_after` isn't set. Secrets are left in the clipboard if it is 0.
+    pub secret_clear_after: u64,
+    /// Whether the get action copies every value to the clipboard, as if `-c` was passed.
+    pub copy: bool,
+    pub color: ColorChoice,
+    /// The actions, along with their arguments, each configured alias expands to.
+    pub aliases: BTreeMap<String, String>,
+}
+
+/// Which database is looked for when none is passed with `--db`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StoreSelection {
+    /// The nearest local database, or else the global one.
+    #[default]
+    Auto,
+    /// Only the nearest local database, with `--local`.
+    Local,
+    /// Only the global database, with `--global`.
+    Global,
+}
+
+/// How the questions asking to confirm an operation are answered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Confirmation {
+    /// Asks the user, unless stdin is not a terminal, in which case nothing is confirmed.
+    #[default]
+    Ask,
+    /// Confirms every operation without asking, with `--yes`.
+    AssumeYes,
+    /// Confirms no operation without asking, with `--no` or `--non-interactive`.
+    AssumeNo,
+}
+
+impl Confirmation {
+    pub fn new(policy: &str) -> Option<Confirmation> {
+        match policy {
+            "ask" => Some(Confirmation::Ask),
+            "yes" => Some(Confirmation::AssumeYes),
+            "no" => Some(Confirmation::AssumeNo),
+            _ => None,
+        }
+    }
+}
+
+/// When the output is highlighted with colors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorChoice {
+    /// Only when printing to a terminal, and never when `NO_COLOR` is set.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    pub fn new(choice: &str) -> Option<ColorChoice> {
+        match choice {
+            "auto" => Some(ColorChoice::Auto),
+            "always" => Some(ColorChoice::Always),
+            "never" => Some(ColorChoice::Never),
+            _ => None,
+        }
+    }
+}
+
+impl Default for ReisbaseGlobalOptions {
+    fn default() -> Self {
+        ReisbaseGlobalOptions {
+            database_path: default_database_path(),
+            configured_database: None,
+            store: StoreSelection::default(),
+            output: OutputFormat::default(),
+            confirmation: Confirmation::default(),
+            clipboard: default_clipboard(),
+            clear_after: default_clear_after(),
+            secret_clear_after: ClipboardConstants::SECRET_CLEAR_AFTER,
+            copy: false,
+            color: ColorChoice::default(),
+            aliases: BTreeMap::new(),
+        }
+    }
+}
+
+impl ReisbaseGlobalOptions {
+    /// The options set in the configuration files, overridden by the ones set in environment
+    /// variables.
+    pub fn configured() -> Result<ReisbaseGlobalOptions, CustomReisIOFailure> {
+        let mut options = ReisbaseGlobalOptions {
+            database_path: None,
+            ..ReisbaseGlobalOptions::default()
+        };
+        let configuration = Configuration::load()?;
+        for warning in configuration.warnings() {
+            ErrorHandler::print_warning(warning);
+        }
+        configuration.apply(&mut options);
+        if let Some(path) = default_database_path() {
+            options.database_path = Some(path);
+        }
+        Ok(options)
+    }
+
+    /// Extracts the global options from `args`, returning them along with the remaining
+    /// arguments, which still describe the requested action. Global options are only read
+    /// before the action: every argument from the first one that isn't a global option is
+    /// left to the action, so keys and values such as `--no` are never taken for options.
+    pub fn parse(
+        args: Vec<String>,
+    ) -> Result<(ReisbaseGlobalOptions, Vec<String>), CustomReisIOFailure> {
+        let mut options = ReisbaseGlobalOptions::configured()?;
+        let mut remaining = Vec::with_capacity(args.len());
+        let mut args = args.into_iter();
+        let mut database_passed = false;
+
+        while let Some(arg) = args.next() {
+            if arg == GlobalOptionsConstants::DATABASE {
+                options.database_path = Some(args.next().ok_or_else(|| {
+                    CustomReisIOFailure::missing_argument_value(GlobalOptionsConstants::DATABASE)
+                })?);
+                database_passed = true;
+            } else if let Some(path) =
+                strip_option_assignment(&arg, GlobalOptionsConstants::DATABASE)
+            {
+                options.database_path = Some(path.to_owned());
+                database_passed = true;
+            } else if arg == GlobalOptionsConstants::LOCAL {
+                options.store = StoreSelection::Local;
+            } else if arg == GlobalOptionsConstants::GLOBAL {
+                options.store = StoreSelection::Global;
+            } else if arg == GlobalOptionsConstants::YES {
+                options.confirmation = Confirmation::AssumeYes;
+            } else if arg == GlobalOptionsConstants::NO
+                || arg == GlobalOptionsConstants::NON_INTERACTIVE
+            {
+                options.confirmation = Confirmation::AssumeNo;
+            } else if arg == GlobalOptionsConstants::OUTPUT {
+                let format = args.next().ok_or_else(|| {
+                    CustomReisIOFailure::missing_argument_value(GlobalOptionsConstants::OUTPUT)
+                })?;
+                options.output = parse_output_format(&format)?;
+            } else if let Some(format) =
+                strip_option_assignment(&arg, GlobalOptionsConstants::OUTPUT)
+            {
+                options.output = parse_output_format(format)?;
+            } else if arg == GlobalOptionsConstants::CLIPBOARD {
+                let selection = args.next().ok_or_else(|| {
+                    CustomReisIOFailure::missing_argument_value(GlobalOptionsConstants::CLIPBOARD)
+                })?;
+                options.clipboard = parse_clipboard(&selection)?;
+            } else if let Some(selection) =
+                strip_option_assignment(&arg, GlobalOptionsConstants::CLIPBOARD)
+            {
+                options.clipboard = parse_clipboard(selection)?;
+            } else {
+                remaining.push(arg);
+                remaining.extend(args);
+                break;
+            }
+        }
+        if options.store != StoreSelection::Auto && !database_passed {
+            options.database_path = None;
+        }
+
+        Ok((options, remaining))
+    }
+}
+
+fn default_database_path() -> Option<String> {
+    env::var(DatabaseStringConstants::DATABASE_PATH_ENV_VAR)
+        .ok()
+        .filter(|path| !path.is_empty())
+}
+
+/// The clipboard backend set in `REISBASE_CLIPBOARD`, or automatic selection if it isn't
+/// set to a known one.
+fn default_clipboard() -> ClipboardSelection {
+    env::var(ClipboardConstants::BACKEND_ENV_VAR)
+        .ok()
+        .and_then(|selection| ClipboardSelection::new(&selection))
+        .unwrap_or_default()
+}
+
+fn default_clear_after() -> Option<u64> {
+    env::var(ClipboardConstants::CLEAR_AFTER_ENV_VAR)
+        .ok()
+        .and_then(|seconds| time::parse_duration(&seconds))
+}
+
+fn parse_clipboard(selection: &str) -> Result<ClipboardSelection, CustomReisIOFailure> {
+    ClipboardSelection::new(selection).ok_or_else(|| {
+        CustomReisIOFailure::invalid_argument_value(GlobalOptionsConstants::CLIPBOARD, selection)
+    })
+}
+
+fn parse_output_format(format: &str) -> Result<OutputFormat, CustomReisIOFailure> {
+    OutputFormat::new(format).ok_or_else(|| {
+        CustomReisIOFailure::invalid_argument_value(GlobalOptionsConstants::OUTPUT, format)
+    })
+}
+
+/// Returns the value of an `--option=value` argument, if `arg` has that shape.
+fn strip_option_assignment<'a>(arg: &'a str, option: &str) -> Option<&'a str> {
+    arg.strip_prefix(option)
+        .and_then(|rest| rest.strip_prefix('='))
+}
@@ -0,0 +1,119 @@
+use std::{
+    fs,
+    io::{self, Read},
+};
+
+use crate::{
+    arguments::ReisbaseActionsArguments,
+    clipboard::{self, ClipboardSelection},
+    failures::CustomReisIOFailure,
+};
+
+/// The value that stands for reading the value from stdin, as in `set key -`.
+pub const STDIN_VALUE: &str = "-";
+
+/// Where the value of an entry is read from, instead of being passed as an argument.
+enum ValueSource<'a> {
+    Stdin,
+    File(&'a str),
+    Clipboard,
+    HiddenPrompt,
+}
+
+impl ValueSource<'_> {
+    fn from_argument(argument: &ReisbaseActionsArguments) -> Option<ValueSource<'_>> {
+        match argument {
+            ReisbaseActionsArguments::FromFile(path) => Some(ValueSource::File(path)),
+            ReisbaseActionsArguments::FromClipboard => Some(ValueSource::Clipboard),
+            ReisbaseActionsArguments::Hidden => Some(ValueSource::HiddenPrompt),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> String {
+        match self {
+            ValueSource::Stdin => String::from("stdin"),
+            ValueSource::File(path) => format!("the file {}", path),
+            ValueSource::Clipboard => String::from("the clipboard"),
+            ValueSource::HiddenPrompt => String::from("the terminal"),
+        }
+    }
+
+    fn read(
+        &self,
+        key: Option<&str>,
+        clipboard: ClipboardSelection,
+    ) -> Result<String, CustomReisIOFailure> {
+        let value = match self {
+            ValueSource::Stdin => {
+                let mut value = String::new();
+                io::stdin()
+                    .read_to_string(&mut value)
+                    .map(|_| without_trailing_newline(value))
+            }
+            ValueSource::File(path) => fs::read_to_string(path).map(without_trailing_newline),
+            ValueSource::Clipboard => return clipboard::paste(clipboard),
+            ValueSource::HiddenPrompt => {
+                rpassword::prompt_password(format!("Value for {}: ", key.unwrap_or("the key")))
+            }
+        };
+        value.map_err(|error| CustomReisIOFailure::value_read_failure(&self.name(), error))
+    }
+}
+
+/// Returns the value of an entry, reading it from where the arguments tell if it wasn't
+/// passed itself. Values read from stdin or a file lose their last line break, so that
+/// `echo value | reis set key -` stores `value`.
+pub fn read_value(
+    action_name: &str,
+    key: Option<&str>,
+    value: Option<String>,
+    arguments: &[ReisbaseActionsArguments],
+    clipboard: ClipboardSelection,
+) -> Result<Option<String>, CustomReisIOFailure> {
+    let mut sources = arguments
+        .iter()
+        .filter_map(ValueSource::from_argument)
+        .collect::<Vec<ValueSource>>();
+    match value {
+        Some(value) if value == STDIN_VALUE => sources.push(ValueSource::Stdin),
+        Some(value) if sources.is_empty() => return Ok(Some(value)),
+        Some(_) => return Err(CustomReisIOFailure::conflicting_value_sources(action_name)),
+        None => {}
+    }
+
+    match sources.as_slice() {
+        [] => Ok(None),
+        [source] => source.read(key, clipboard).map(Some),
+        _ => Err(CustomReisIOFailure::conflicting_value_sources(action_name)),
+    }
+}
+
+/// Reads the contents of the file at `path`, or of stdin if it is [`STDIN_VALUE`].
+pub fn read_contents(path: &str) -> Result<String, CustomReisIOFailure> {
+    let source = if path == STDIN_VALUE {
+        ValueSource::Stdin
+    } else {
+        ValueSource::File(path)
+    };
+    source.read(None, ClipboardSelection::default())
+}
+
+/// The name of the file at `path` in messages, which is stdin for [`STDIN_VALUE`].
+pub fn source_name(path: &str) -> &str {
+    if path == STDIN_VALUE {
+        "stdin"
+    } else {
+        path
+    }
+}
+
+fn without_trailing_newline(mut value: String) -> String {
+    if value.ends_with('\n') {
+        value.pop();
+        if value.ends_with('\r') {
+            value.pop();
+        }
+    }
+    value
+}
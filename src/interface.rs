@@ -4,6 +4,7 @@ use crate::{
     controller::Controller,
     error_handler::ErrorHandler,
     failures::{CustomFailureOperation, CustomReisIOFailure},
+    global_options::ReisbaseGlobalOptions,
     operation::Operation,
     success::CustomSuccessOperation,
 };
@@ -19,27 +20,32 @@ impl Interface {
         key: Option<String>,
         value: Option<String>,
         arguments: Vec<String>,
+        options: &ReisbaseGlobalOptions,
     ) -> Result<Interface, CustomReisIOFailure> {
-        Controller::new(action, key, value, arguments).map(|controller| Interface { controller })
+        Controller::new(action, key, value, arguments, options)
+            .map(|controller| Interface { controller })
     }
 
     pub fn execute(
         operation: Option<Operation>,
+        options: &ReisbaseGlobalOptions,
     ) -> Result<CustomSuccessOperation, CustomFailureOperation> {
         operation
             .ok_or_else(build_empty_action_error)
-            .and_then(create_interface_and_map_error)
+            .and_then(|operation| create_interface_and_map_error(operation, options))
             .and_then(execute_action)
     }
 }
 fn create_interface_and_map_error(
     operation: Operation,
+    options: &ReisbaseGlobalOptions,
 ) -> Result<Interface, CustomFailureOperation> {
     Interface::new(
         &operation.action,
         operation.key,
         operation.value,
         operation.arguments,
+        options,
     )
     .map_err(CustomFailureOperation::Error)
 }
@@ -54,8 +60,5 @@ fn build_empty_action_error() -> CustomFailureOperation {
 fn execute_action(
     mut interface: Interface,
 ) -> Result<CustomSuccessOperation, CustomFailureOperation> {
-    interface
-        .controller
-        .execute()
-        .map_err(CustomFailureOperation::Warning)
+    interface.controller.execute()
 }
@@ -0,0 +1,139 @@
+//! Reading through a stack of databases.
+//!
+//! The database an action writes to is the top layer, and the databases below it, as found by
+//! [`DatabaseLocation::lower_layers`], are read after it. A key in a layer shadows the same
+//! key in every layer below, so reads answer with the closest layer that has it. The lower
+//! layers are only read: writes always go to the top layer.
+//!
+//! A lower layer is only read once a lookup reaches it, so the passphrase of an encrypted one
+//! is only asked for when the layers above it don't have the key. A lower layer that can't be
+//! read, because it is corrupt or its passphrase is wrong, is skipped with a warning instead
+//! of failing the lookup.
+
+use std::{cell::OnceCell, collections::BTreeMap, iter};
+
+use serde::Serialize;
+
+use crate::{
+    constants::the_layer_is_skipped,
+    entry::{KeyedEntry, ReisbaseEntry},
+    error_handler::ErrorHandler,
+    location::{DatabaseLocation, LocationSource},
+    namespace::NamespaceScope,
+    reisbase::Reisbase,
+};
+
+/// A database below the one an action writes to, which is read the first time it is needed.
+#[derive(Debug)]
+pub struct Layer {
+    pub location: DatabaseLocation,
+    database: OnceCell<Option<Reisbase>>,
+}
+
+impl Layer {
+    /// The databases below `location`, from the closest, none of which is read yet.
+    pub fn below(location: &DatabaseLocation) -> Vec<Layer> {
+        location
+            .lower_layers()
+            .into_iter()
+            .map(|location| Layer {
+                location,
+                database: OnceCell::new(),
+            })
+            .collect()
+    }
+
+    /// Reads the database of this layer, or returns [`None`] after warning that it is
+    /// skipped if it can't be read.
+    pub fn database(&self) -> Option<&Reisbase> {
+        self.database
+            .get_or_init(|| match Reisbase::build(&self.location.path) {
+                Ok(database) => Some(database),
+                Err(error) => {
+                    ErrorHandler::print_warning(&the_layer_is_skipped(
+                        &self.location.path,
+                        error.error_message().message(),
+                    ));
+                    None
+                }
+            })
+            .as_ref()
+    }
+}
+
+/// An entry along with the layer it was read from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LayeredEntry {
+    #[serde(flatten)]
+    pub keyed: KeyedEntry,
+    pub source: LocationSource,
+}
+
+/// A read-only view of a stack of databases, from the one that takes precedence.
+pub struct LayeredView<'a> {
+    location: &'a DatabaseLocation,
+    database: &'a Reisbase,
+    lower_layers: &'a [Layer],
+}
+
+impl<'a> LayeredView<'a> {
+    pub fn new(
+        location: &'a DatabaseLocation,
+        database: &'a Reisbase,
+        lower_layers: &'a [Layer],
+    ) -> LayeredView<'a> {
+        LayeredView {
+            location,
+            database,
+            lower_layers,
+        }
+    }
+
+    /// Returns the entry for `key` in the closest layer that has it, along with that layer.
+    /// The layers below it aren't read.
+    pub fn get_entry(&self, key: &str) -> Option<(&'a DatabaseLocation, &'a ReisbaseEntry)> {
+        self.get_entries(key).next()
+    }
+
+    /// Returns the entry for `key` in every layer that has it, from the one that answers to
+    /// the ones it shadows. Each layer is only read once the iteration reaches it.
+    pub fn get_entries<'k>(
+        &self,
+        key: &'k str,
+    ) -> impl Iterator<Item = (&'a DatabaseLocation, &'a ReisbaseEntry)> + 'k
+    where
+        'a: 'k,
+    {
+        self.layers().filter_map(move |(location, database)| {
+            database.get_entry(key).map(|entry| (location, entry))
+        })
+    }
+
+    /// Returns the effective entries in `scope`, sorted by key: the ones of every layer,
+    /// except those shadowed by a closer layer.
+    pub fn get_all(&self, scope: &NamespaceScope) -> Vec<LayeredEntry> {
+        let mut merged = BTreeMap::new();
+        let layers = self.layers().collect::<Vec<_>>();
+        for (location, database) in layers.into_iter().rev() {
+            for keyed in database.get_all(scope) {
+                merged.insert(
+                    keyed.key.clone(),
+                    LayeredEntry {
+                        keyed,
+                        source: location.source,
+                    },
+                );
+            }
+        }
+        merged.into_values().collect()
+    }
+
+    /// Every readable layer, from the one that takes precedence, each read as it is reached.
+    fn layers(&self) -> impl Iterator<Item = (&'a DatabaseLocation, &'a Reisbase)> {
+        iter::once((self.location, self.database)).chain(
+            self.lower_layers
+                .iter()
+                .filter_map(|layer| layer.database().map(|database| (&layer.location, database))),
+        )
+    }
+}
@@ -0,0 +1,191 @@
+//! Finding the database an invocation works with.
+//!
+//! A database passed with `--db` or set in `REISBASE_DB` is always used as is, and so is the
+//! one set in the configuration. Otherwise the nearest local database is used: a
+//! [`DatabaseStringConstants::LOCAL_DATABASE_NAME`] file in the current directory or any of
+//! its parents, like `git` finds its repository. Without a local database, the global one
+//! under `$XDG_DATA_HOME/reisbase` is used. `--local` and `--global` only look for one of the
+//! two, even when a database is configured or set in `REISBASE_DB`. A [`DatabaseStringConstants::DATABASE_NAME`]
+//! file in the current directory, which is where databases used to be kept, is no longer
+//! used, but is reported so it isn't silently left behind.
+//!
+//! Reads can also go through the databases below the one in use, which are its lower
+//! [layers](crate::layers): the global database below a local one, and the read-only system
+//! database at [`DatabaseStringConstants::SYSTEM_DATABASE_PATH`] below both.
+
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+
+use crate::{
+    constants::DatabaseStringConstants,
+    failures::CustomReisIOFailure,
+    global_options::{ReisbaseGlobalOptions, StoreSelection},
+};
+
+/// How the database was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LocationSource {
+    /// Passed with `--db` or set in `REISBASE_DB`.
+    Explicit,
+    /// Set with the `database` setting of the configuration.
+    Configured,
+    Local,
+    Global,
+    /// The read-only database shared by every user, which is only ever a lower layer.
+    System,
+}
+
+impl std::fmt::Display for LocationSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LocationSource::Explicit => write!(f, "explicit"),
+            LocationSource::Configured => write!(f, "configured"),
+            LocationSource::Local => write!(f, "local"),
+            LocationSource::Global => write!(f, "global"),
+            LocationSource::System => write!(f, "system"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DatabaseLocation {
+    pub path: String,
+    pub source: LocationSource,
+}
+
+impl DatabaseLocation {
+    /// Finds the database to read and write, as described in the [module docs](self).
+    pub fn resolve(
+        options: &ReisbaseGlobalOptions,
+    ) -> Result<DatabaseLocation, CustomReisIOFailure> {
+        if let Some(location) = explicit_location(options).or_else(|| configured_location(options))
+        {
+            return Ok(location);
+        }
+        match options.store {
+            StoreSelection::Auto => match find_local_database()? {
+                Some(path) => Ok(DatabaseLocation::new(path, LocationSource::Local)),
+                None => global_location(),
+            },
+            StoreSelection::Local => find_local_database()?
+                .map(|path| DatabaseLocation::new(path, LocationSource::Local))
+                .ok_or_else(CustomReisIOFailure::local_database_not_found),
+            StoreSelection::Global => global_location(),
+        }
+    }
+
+    /// The database in the current directory that was used before databases were looked
+    /// for, if there is one and it isn't this one. Only looked for when no store or database
+    /// was requested.
+    pub fn ignored_legacy_database(&self, options: &ReisbaseGlobalOptions) -> Option<String> {
+        let looked_for = matches!(self.source, LocationSource::Local | LocationSource::Global);
+        if !looked_for || options.store != StoreSelection::Auto {
+            return None;
+        }
+        let path = env::current_dir()
+            .ok()?
+            .join(DatabaseStringConstants::DATABASE_NAME);
+        (path.is_file() && path != Path::new(&self.path))
+            .then(|| path.to_string_lossy().into_owned())
+    }
+
+    /// Where a new database is created by the init action: in the current directory, unless
+    /// the global one or a path is requested, or a database is configured.
+    pub fn for_init(
+        options: &ReisbaseGlobalOptions,
+    ) -> Result<DatabaseLocation, CustomReisIOFailure> {
+        if let Some(location) = explicit_location(options).or_else(|| configured_location(options))
+        {
+            return Ok(location);
+        }
+        match options.store {
+            StoreSelection::Auto | StoreSelection::Local => {
+                let path = current_dir()?.join(DatabaseStringConstants::LOCAL_DATABASE_NAME);
+                Ok(DatabaseLocation::new(path, LocationSource::Local))
+            }
+            StoreSelection::Global => global_location(),
+        }
+    }
+
+    /// The existing databases below this one, from the closest. A database passed with `--db`
+    /// has none, while a configured one has the same ones as a local database.
+    pub fn lower_layers(&self) -> Vec<DatabaseLocation> {
+        let below = match self.source {
+            LocationSource::Explicit | LocationSource::System => vec![],
+            LocationSource::Local | LocationSource::Configured => {
+                vec![global_location().ok(), Some(system_location())]
+            }
+            LocationSource::Global => vec![Some(system_location())],
+        };
+        below
+            .into_iter()
+            .flatten()
+            .filter(|location| location.path != self.path && Path::new(&location.path).is_file())
+            .collect()
+    }
+
+    fn new(path: PathBuf, source: LocationSource) -> DatabaseLocation {
+        DatabaseLocation {
+            path: path.to_string_lossy().into_owned(),
+            source,
+        }
+    }
+}
+
+fn explicit_location(options: &ReisbaseGlobalOptions) -> Option<DatabaseLocation> {
+    options
+        .database_path
+        .as_ref()
+        .map(|path| DatabaseLocation::new(PathBuf::from(path), LocationSource::Explicit))
+}
+
+/// The database set in the configuration, unless a store is requested.
+fn configured_location(options: &ReisbaseGlobalOptions) -> Option<DatabaseLocation> {
+    options
+        .configured_database
+        .as_ref()
+        .filter(|_| options.store == StoreSelection::Auto)
+        .map(|path| DatabaseLocation::new(PathBuf::from(path), LocationSource::Configured))
+}
+
+/// Returns the nearest local database, walking up from the current directory.
+fn find_local_database() -> Result<Option<PathBuf>, CustomReisIOFailure> {
+    Ok(current_dir()?
+        .ancestors()
+        .map(|directory| directory.join(DatabaseStringConstants::LOCAL_DATABASE_NAME))
+        .find(|path| path.is_file()))
+}
+
+fn global_location() -> Result<DatabaseLocation, CustomReisIOFailure> {
+    let data_home = env::var_os("XDG_DATA_HOME")
+        .filter(|path| Path::new(path).is_absolute())
+        .map(PathBuf::from)
+        .or_else(|| {
+            env::var_os("HOME")
+                .filter(|home| !home.is_empty())
+                .map(|home| Path::new(&home).join(".local").join("share"))
+        })
+        .ok_or_else(CustomReisIOFailure::global_database_unavailable)?;
+    let path = data_home
+        .join(DatabaseStringConstants::GLOBAL_DATABASE_DIRECTORY)
+        .join(DatabaseStringConstants::DATABASE_NAME);
+    Ok(DatabaseLocation::new(path, LocationSource::Global))
+}
+
+/// The system database set in `REISBASE_SYSTEM_DB`, or else the default one.
+fn system_location() -> DatabaseLocation {
+    let path = env::var_os(DatabaseStringConstants::SYSTEM_DATABASE_PATH_ENV_VAR)
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(DatabaseStringConstants::SYSTEM_DATABASE_PATH));
+    DatabaseLocation::new(path, LocationSource::System)
+}
+
+fn current_dir() -> Result<PathBuf, CustomReisIOFailure> {
+    env::current_dir().map_err(CustomReisIOFailure::current_directory_unavailable)
+}
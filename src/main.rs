@@ -1,18 +1,38 @@
+use std::process::ExitCode;
+
 use terminal_communication::TerminalCommunication;
 
 pub mod actions;
 pub mod arguments;
+pub mod clipboard;
+pub mod config;
 pub mod constants;
 pub mod controller;
+pub mod database_format;
+pub mod encryption;
+pub mod entry;
+pub mod environment;
 pub mod error_handler;
+pub mod exchange;
 pub mod extensions;
 pub mod failures;
+pub mod global_options;
+pub mod input;
 pub mod interface;
+pub mod layers;
+pub mod location;
+pub mod namespace;
 pub mod operation;
+pub mod output;
 pub mod reisbase;
+pub mod search;
+pub mod storage;
 pub mod success;
+pub mod suggestions;
+pub mod template;
 pub mod terminal_communication;
+pub mod time;
 
-fn main() {
+fn main() -> ExitCode {
     TerminalCommunication::execute()
 }
@@ -0,0 +1,94 @@
+//! Namespaces group the entries of a database under a shared name.
+//!
+//! A key is in a namespace when it is written as `namespace:key`, or when the namespace is
+//! passed with `--ns`. Keys without a namespace are in the default one, which has no name.
+//! Only the first separator splits the namespace from the key, so keys inside a namespace may
+//! still contain it.
+//!
+//! A key is only in a namespace while that namespace is declared, which only happens
+//! explicitly: with the namespace actions, or by setting or importing keys with `--ns`. The
+//! name must be [valid](validate). Keys that merely contain the separator, such as
+//! `http://host` or the keys of databases written before namespaces existed, stay in the
+//! default namespace. They only move into a namespace once one is created with the name before
+//! their first separator.
+
+use serde::Serialize;
+
+use crate::{constants::DatabaseStringConstants, failures::CustomReisIOFailure};
+
+/// A namespace along with how many entries it holds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NamespaceSummary {
+    pub name: String,
+    pub count: usize,
+}
+
+/// Which entries an action that works on many of them applies to.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum NamespaceScope {
+    /// The entries without a namespace.
+    #[default]
+    Default,
+    Named(String),
+    /// Every entry, regardless of its namespace.
+    All,
+}
+
+impl NamespaceScope {
+    /// Whether a key in `namespace`, or in the default one if it is [`None`], belongs to
+    /// this scope.
+    pub fn contains(&self, namespace: Option<&str>) -> bool {
+        match self {
+            NamespaceScope::Default => namespace.is_none(),
+            NamespaceScope::Named(named) => namespace == Some(named),
+            NamespaceScope::All => true,
+        }
+    }
+}
+
+/// Returns `key` inside `namespace`, or `key` itself if no namespace is given.
+pub fn qualify(key: &str, namespace: Option<&str>) -> String {
+    match namespace {
+        Some(namespace) => format!(
+            "{}{}{}",
+            namespace,
+            DatabaseStringConstants::NAMESPACE_SEPARATOR,
+            key
+        ),
+        None => key.to_owned(),
+    }
+}
+
+/// Returns the namespace `key` is written in, or [`None`] if it has no separator. The key is
+/// only in that namespace if it is declared.
+pub fn namespace_of(key: &str) -> Option<&str> {
+    key.split_once(DatabaseStringConstants::NAMESPACE_SEPARATOR)
+        .map(|(namespace, _)| namespace)
+}
+
+/// Returns `key` without the namespace it is in.
+pub fn local_key(key: &str) -> &str {
+    key.split_once(DatabaseStringConstants::NAMESPACE_SEPARATOR)
+        .map_or(key, |(_, key)| key)
+}
+
+/// Returns `key` moved from the namespace it is in to `namespace`.
+pub fn rename(key: &str, namespace: &str) -> String {
+    qualify(local_key(key), Some(namespace))
+}
+
+/// Checks that `namespace` can be used as the name of a namespace: it can't be empty, nor
+/// contain the separator or whitespace.
+pub fn validate(namespace: &str) -> Result<&str, CustomReisIOFailure> {
+    let is_valid = !namespace.is_empty()
+        && !namespace.contains(DatabaseStringConstants::NAMESPACE_SEPARATOR)
+        && !namespace.contains(char::is_whitespace);
+    if is_valid {
+        Ok(namespace)
+    } else {
+        Err(CustomReisIOFailure::invalid_argument_value(
+            "namespace",
+            namespace,
+        ))
+    }
+}
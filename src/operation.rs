@@ -1,3 +1,5 @@
+use crate::arguments::ReisbaseActionsArguments;
+
 pub struct Operation {
     pub action: String,
     pub key: Option<String>,
@@ -20,21 +22,60 @@ impl Operation {
         })
     }
 
-    pub fn put(key: &str, value: &str) -> Operation {
+    pub fn put(key: &str, value: &str, arguments: &[ReisbaseActionsArguments]) -> Operation {
         Operation {
             action: String::from("put"),
             key: Some(key.to_owned()),
             value: Some(value.to_owned()),
-            arguments: Vec::new(),
+            arguments: retried_arguments(arguments),
+        }
+    }
+
+    /// Requests `action` again, for a different key than the one it was first requested with.
+    pub fn with_key(
+        action: &str,
+        key: &str,
+        value: Option<&str>,
+        arguments: &[ReisbaseActionsArguments],
+    ) -> Operation {
+        Operation {
+            action: action.to_owned(),
+            key: Some(key.to_owned()),
+            value: value.map(String::from),
+            arguments: retried_arguments(arguments),
         }
     }
 
-    pub fn clear() -> Operation {
+    /// Requests `action` again with the same key and arguments, confirming it with `-f`.
+    pub fn forced(
+        action: &str,
+        key: Option<&str>,
+        arguments: &[ReisbaseActionsArguments],
+    ) -> Operation {
+        let mut arguments = arguments
+            .iter()
+            .flat_map(ReisbaseActionsArguments::to_args)
+            .collect::<Vec<String>>();
+        arguments.extend(ReisbaseActionsArguments::Force.to_args());
         Operation {
-            action: String::from("c"),
-            key: None,
+            action: action.to_owned(),
+            key: key.map(String::from),
             value: None,
-            arguments: vec![String::from("-f")],
+            arguments,
         }
     }
 }
+
+/// The arguments to request an operation again with. Arguments telling where the value was
+/// read from are left out, since the value is passed along with them, and so is the
+/// namespace, since the key already includes it.
+fn retried_arguments(arguments: &[ReisbaseActionsArguments]) -> Vec<String> {
+    arguments
+        .iter()
+        .filter(|argument| {
+            !argument.is_value_source()
+                && !matches!(argument, ReisbaseActionsArguments::Namespace(_))
+        })
+        .flat_map(ReisbaseActionsArguments::to_args)
+        .collect()
+}
@@ -0,0 +1,621 @@
+//! Formats the outcome of an operation for the `--output` global option.
+//!
+//! `plain` prints the messages meant to be read by people, and is the only format that asks
+//! for confirmation before retrying an operation. The other formats never prompt, so they can
+//! be used from scripts.
+//!
+//! `json` prints a single object per operation, on a single line:
+//!
+//! ```text
+//! {"status":"success","operation":"<operation>","data":{...}}
+//! {"status":"warning","warning":"<warning>","message":"...","data":{...}}
+//! {"status":"error","error":"<error>","message":"...","details":"..."}
+//! ```
+//!
+//! The `data` of each operation is:
+//!
+//! | operation        | data                                                                 |
+//! |------------------|----------------------------------------------------------------------|
+//! | `set`            | `key`, `value`                                                       |
+//! | `get`            | `key`, `value`, `description`, `expires_at`, `secret`                |
+//! | `explain`        | `key`, `value`, `description`, `expires_at`, `secret`, `path`, `source`, `shadowed`: list of `path`, `source` |
+//! | `put`            | `key`, `value`, `old_value`                                          |
+//! | `delete`         | `key`, `old_value`                                                   |
+//! | `get_all`        | `entries`: list of `key`, `value`, `description`, `expires_at`, `secret` |
+//! | `get_merged`     | `entries`: list of `key`, `value`, `description`, `expires_at`, `secret`, `source` |
+//! | `find`           | `matches`: list of `key`, `value`, `description`, `key_matches`, `value_matches`, `description_matches` |
+//! | `clear`          | `count`                                                              |
+//! | `ttl`            | `key`, `expires_at`, `remaining`                                     |
+//! | `history`        | `key`, `current`, `versions`: list of `value`, `description`, `expires_at`, `secret`, `replaced_at` |
+//! | `revert`         | `key`, `version`, `value`, `description`, `expires_at`, `secret`     |
+//! | `undo`           | `restored`                                                           |
+//! | `namespaces`     | `namespaces`: list of `name`, `count`                                |
+//! | `create_namespace` | `namespace`                                                        |
+//! | `rename_namespace` | `namespace`, `new_namespace`, `moved`                              |
+//! | `drop_namespace` | `namespace`, `deleted`                                               |
+//! | `restore_backup` | `backup_path`                                                        |
+//! | `export`         | `format`, `count`, `contents`                                        |
+//! | `import`         | `source`, `dry_run`, `changes`: list of `key`, `value`, `change`     |
+//! | `run`            | `command`, `exit_code`                                               |
+//! | `env`            | `shell`, `variables`: list of `name`, `key`, `value`                 |
+//! | `render`         | `source`, `contents`, `missing`                                      |
+//! | `config`         | `settings`: list of `name`, `value`, `scope`                         |
+//! | `config_get`     | `name`, `value`, `scope`                                             |
+//! | `config_set`     | `name`, `value`, `path`, `scope`                                     |
+//! | `init`           | `path`, `source`                                                     |
+//! | `which`          | `path`, `source`                                                     |
+//! | `encrypt`        | `path`                                                               |
+//! | `decrypt`        | `path`                                                               |
+//! | `rekey`          | `path`                                                               |
+//! | `repair`         | `quarantined`, `quarantine_path`                                     |
+//!
+//! The `data` of each warning is:
+//!
+//! | warning                            | data                                           |
+//! |------------------------------------|------------------------------------------------|
+//! | `empty_database`                   | `null`                                         |
+//! | `no_matching_entries`              | `null`                                         |
+//! | `nothing_to_undo`                  | `null`                                         |
+//! | `already_encrypted`                | `null`                                         |
+//! | `not_encrypted`                    | `null`                                         |
+//! | `no_namespaces`                    | `null`                                         |
+//! | `no_settings`                      | `null`                                         |
+//! | `setting_not_set`                  | `setting`                                      |
+//! | `database_already_exists`          | `path`                                         |
+//! | `namespace_already_exists`         | `namespace`                                    |
+//! | `namespace_doesnt_exist`           | `namespace`                                    |
+//! | `entry_already_exists`             | `key`, `old_value`, `new_value`                |
+//! | `entry_doesnt_exist`               | `key`, `value`, `action`, `suggestions`        |
+//! | `import_conflicts`                 | `source`, `imported`, `conflicts`: list of `key`, `old_value`, `new_value` |
+//! | `conflicting_variables`            | `variable`, `keys`                             |
+//! | `invalid_variable_name`            | `key`                                          |
+//! | `missing_template_keys`            | `source`, `keys`                               |
+//! | `secrets_not_revealed`             | `keys`                                         |
+//! | `no_history`                       | `key`                                          |
+//! | `history_version_doesnt_exist`     | `key`, `version`                               |
+//! | `required_arguments_not_specified` | `operation`: `action`, `arguments`             |
+//!
+//! Errors are identified by the snake case name of their [`CustomReisIOFailure`] variant,
+//! such as `corrupted_database`. Missing values are `null`, timestamps are seconds since the
+//! Unix epoch, durations are in seconds and match ranges are `start`/`end` byte offsets.
+//! Keys inside a namespace are always shown with their `namespace:` prefix. The `source` of a
+//! database is how it was found: `explicit`, `configured`, `local` or `global`, or `system`
+//! for the read-only database below them. The `scope` of a setting is the configuration file
+//! it is set in: `user` or `project`. The `change` an import makes to an entry is `added`,
+//! `overwritten`, `skipped` or `conflict`.
+//! The report of `run` is printed after the output of the command it ran, and reisbase exits
+//! with the status of that command. In `plain`, nothing but the output of the command is
+//! printed.
+//! The values of secret entries are masked in every format, unless they are revealed with
+//! `--reveal`.
+//!
+//! `tsv` prints the `data` of a successful operation as tab separated rows, with the same
+//! fields in the same order, escaped like the database file. Lists print one row per item,
+//! and the versions of `history` start with their number. Warnings and errors are printed
+//! to stderr as a `warning` or `error` row, followed by their name and message.
+
+use serde::Serialize;
+
+use crate::{
+    database_format,
+    failures::{CustomReisActionWarning, CustomReisIOFailure},
+    success::CustomSuccessOperation,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    #[default]
+    Plain,
+    Json,
+    Tsv,
+}
+
+impl OutputFormat {
+    pub fn new(format: &str) -> Option<OutputFormat> {
+        match format {
+            "plain" => Some(OutputFormat::Plain),
+            "json" => Some(OutputFormat::Json),
+            "tsv" => Some(OutputFormat::Tsv),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum Report<'a> {
+    Success {
+        operation: &'static str,
+        data: &'a CustomSuccessOperation,
+    },
+    Warning {
+        warning: &'static str,
+        message: String,
+        data: &'a CustomReisActionWarning,
+    },
+    Error {
+        error: &'static str,
+        message: &'a str,
+        details: String,
+    },
+}
+
+impl Report<'_> {
+    fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("reports only hold serializable data")
+    }
+}
+
+pub fn success_to_json(success: &CustomSuccessOperation) -> String {
+    Report::Success {
+        operation: success.kind(),
+        data: success,
+    }
+    .to_json()
+}
+
+pub fn warning_to_json(warning: &CustomReisActionWarning) -> String {
+    Report::Warning {
+        warning: warning.kind(),
+        message: warning.message(),
+        data: warning,
+    }
+    .to_json()
+}
+
+pub fn error_to_json(error: &CustomReisIOFailure) -> String {
+    let error_message = error.error_message();
+    Report::Error {
+        error: error.kind(),
+        message: error_message.message(),
+        details: error_message.error().to_string(),
+    }
+    .to_json()
+}
+
+pub fn success_to_tsv(success: &CustomSuccessOperation) -> String {
+    success
+        .rows()
+        .iter()
+        .map(|row| tsv_row(row))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+pub fn warning_to_tsv(warning: &CustomReisActionWarning) -> String {
+    tsv_row(&["warning", warning.kind(), &warning.message()])
+}
+
+pub fn error_to_tsv(error: &CustomReisIOFailure) -> String {
+    tsv_row(&["error", error.kind(), error.error_message().message()])
+}
+
+fn tsv_row<S: AsRef<str>>(cells: &[S]) -> String {
+    cells
+        .iter()
+        .map(|cell| database_format::escape(cell.as_ref()))
+        .collect::<Vec<String>>()
+        .join("\t")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::{
+        actions::ReisbaseAction,
+        arguments::ReisbaseActionsArguments,
+        config::{ConfigScope, ConfiguredSetting, Setting},
+        entry::{HistoryVersion, KeyedEntry, ReisbaseEntry},
+        environment::{EnvironmentVariable, Shell},
+        exchange::{ExchangeFormat, ImportChange, ImportChangeKind},
+        layers::LayeredEntry,
+        location::{DatabaseLocation, LocationSource},
+        namespace::NamespaceSummary,
+        search::SearchMatch,
+    };
+
+    fn entry() -> ReisbaseEntry {
+        ReisbaseEntry {
+            value: String::from("localhost"),
+            description: Some(String::from("the\thost")),
+            expires_at: Some(1_700_000_000),
+            secret: false,
+        }
+    }
+
+    fn location(path: &str, source: LocationSource) -> DatabaseLocation {
+        DatabaseLocation {
+            path: String::from(path),
+            source,
+        }
+    }
+
+    fn setting() -> ConfiguredSetting {
+        ConfiguredSetting::new(
+            Setting::Output,
+            "json",
+            ConfigScope::User,
+            Path::new("/home"),
+        )
+    }
+
+    /// One operation of each kind, along with its JSON report and its TSV rows.
+    fn successes() -> Vec<(CustomSuccessOperation, &'static str, &'static str)> {
+        vec![
+            (
+                CustomSuccessOperation::insert("db:host", "localhost"),
+                r#"{"status":"success","operation":"set","data":{"key":"db:host","value":"localhost"}}"#,
+                "db:host\tlocalhost",
+            ),
+            (
+                CustomSuccessOperation::get("host", &entry(), true),
+                r#"{"status":"success","operation":"get","data":{"key":"host","value":"localhost","description":"the\thost","expires_at":1700000000,"secret":false}}"#,
+                "host\tlocalhost\tthe\\thost\t1700000000\tfalse",
+            ),
+            (
+                CustomSuccessOperation::Explain {
+                    key: String::from("host"),
+                    entry: ReisbaseEntry::new("localhost"),
+                    layer: location("/work/.reis/reis.db", LocationSource::Local),
+                    shadowed: vec![location(
+                        "/home/.local/share/reis.db",
+                        LocationSource::Global,
+                    )],
+                },
+                r#"{"status":"success","operation":"explain","data":{"key":"host","value":"localhost","description":null,"expires_at":null,"secret":false,"path":"/work/.reis/reis.db","source":"local","shadowed":[{"path":"/home/.local/share/reis.db","source":"global"}]}}"#,
+                "host\tlocalhost\t\t\tfalse\t/work/.reis/reis.db\tlocal",
+            ),
+            (
+                CustomSuccessOperation::put("host", "127.0.0.1", "localhost"),
+                r#"{"status":"success","operation":"put","data":{"key":"host","value":"127.0.0.1","old_value":"localhost"}}"#,
+                "host\t127.0.0.1\tlocalhost",
+            ),
+            (
+                CustomSuccessOperation::delete("host", "localhost"),
+                r#"{"status":"success","operation":"delete","data":{"key":"host","old_value":"localhost"}}"#,
+                "host\tlocalhost",
+            ),
+            (
+                CustomSuccessOperation::GetAll {
+                    entries: vec![
+                        KeyedEntry {
+                            key: String::from("host"),
+                            entry: ReisbaseEntry::new("localhost"),
+                        },
+                        KeyedEntry {
+                            key: String::from("port"),
+                            entry: ReisbaseEntry::new("5432"),
+                        },
+                    ],
+                },
+                r#"{"status":"success","operation":"get_all","data":{"entries":[{"key":"host","value":"localhost","description":null,"expires_at":null,"secret":false},{"key":"port","value":"5432","description":null,"expires_at":null,"secret":false}]}}"#,
+                "host\tlocalhost\t\t\tfalse\nport\t5432\t\t\tfalse",
+            ),
+            (
+                CustomSuccessOperation::GetMerged {
+                    entries: vec![LayeredEntry {
+                        keyed: KeyedEntry {
+                            key: String::from("host"),
+                            entry: ReisbaseEntry::new("localhost"),
+                        },
+                        source: LocationSource::System,
+                    }],
+                },
+                r#"{"status":"success","operation":"get_merged","data":{"entries":[{"key":"host","value":"localhost","description":null,"expires_at":null,"secret":false,"source":"system"}]}}"#,
+                "host\tlocalhost\t\t\tfalse\tsystem",
+            ),
+            (
+                CustomSuccessOperation::Find {
+                    matches: vec![SearchMatch {
+                        key: String::from("host"),
+                        value: String::from("localhost"),
+                        description: None,
+                        key_matches: vec![],
+                        value_matches: vec![0..5, 6..9],
+                        description_matches: vec![],
+                    }],
+                },
+                r#"{"status":"success","operation":"find","data":{"matches":[{"key":"host","value":"localhost","description":null,"key_matches":[],"value_matches":[{"start":0,"end":5},{"start":6,"end":9}],"description_matches":[]}]}}"#,
+                "host\tlocalhost\t",
+            ),
+            (
+                CustomSuccessOperation::clear(2),
+                r#"{"status":"success","operation":"clear","data":{"count":2}}"#,
+                "2",
+            ),
+            (
+                CustomSuccessOperation::ttl("host", Some(1_700_000_060), 1_700_000_000),
+                r#"{"status":"success","operation":"ttl","data":{"key":"host","expires_at":1700000060,"remaining":60}}"#,
+                "host\t1700000060\t60",
+            ),
+            (
+                CustomSuccessOperation::history(
+                    "pw",
+                    Some(&ReisbaseEntry {
+                        secret: true,
+                        ..ReisbaseEntry::new("hunter2")
+                    }),
+                    &[&HistoryVersion {
+                        entry: ReisbaseEntry::new("hunter1"),
+                        replaced_at: 1_700_000_000,
+                    }],
+                ),
+                r#"{"status":"success","operation":"history","data":{"key":"pw","current":{"value":"********","description":null,"expires_at":null,"secret":true},"versions":[{"value":"hunter1","description":null,"expires_at":null,"secret":false,"replaced_at":1700000000}]}}"#,
+                "1\thunter1\t\t\tfalse\t1700000000",
+            ),
+            (
+                CustomSuccessOperation::revert("host", 1, ReisbaseEntry::new("localhost")),
+                r#"{"status":"success","operation":"revert","data":{"key":"host","version":1,"value":"localhost","description":null,"expires_at":null,"secret":false}}"#,
+                "host\tlocalhost\t\t\tfalse\t1",
+            ),
+            (
+                CustomSuccessOperation::undo(1),
+                r#"{"status":"success","operation":"undo","data":{"restored":1}}"#,
+                "1",
+            ),
+            (
+                CustomSuccessOperation::Namespaces {
+                    namespaces: vec![NamespaceSummary {
+                        name: String::from("db"),
+                        count: 2,
+                    }],
+                },
+                r#"{"status":"success","operation":"namespaces","data":{"namespaces":[{"name":"db","count":2}]}}"#,
+                "db\t2",
+            ),
+            (
+                CustomSuccessOperation::CreateNamespace {
+                    namespace: String::from("db"),
+                },
+                r#"{"status":"success","operation":"create_namespace","data":{"namespace":"db"}}"#,
+                "db",
+            ),
+            (
+                CustomSuccessOperation::RenameNamespace {
+                    namespace: String::from("db"),
+                    new_namespace: String::from("database"),
+                    moved: 2,
+                },
+                r#"{"status":"success","operation":"rename_namespace","data":{"namespace":"db","new_namespace":"database","moved":2}}"#,
+                "db\tdatabase\t2",
+            ),
+            (
+                CustomSuccessOperation::DropNamespace {
+                    namespace: String::from("db"),
+                    deleted: 2,
+                },
+                r#"{"status":"success","operation":"drop_namespace","data":{"namespace":"db","deleted":2}}"#,
+                "db\t2",
+            ),
+            (
+                CustomSuccessOperation::restore_backup("reis.db.bak"),
+                r#"{"status":"success","operation":"restore_backup","data":{"backup_path":"reis.db.bak"}}"#,
+                "reis.db.bak",
+            ),
+            (
+                CustomSuccessOperation::Export {
+                    format: ExchangeFormat::Env,
+                    count: 1,
+                    contents: String::from("HOST=\"localhost\"\n"),
+                },
+                r#"{"status":"success","operation":"export","data":{"format":"env","count":1,"contents":"HOST=\"localhost\"\n"}}"#,
+                "env\t1\tHOST=\"localhost\"\\n",
+            ),
+            (
+                CustomSuccessOperation::Import {
+                    source: String::from("entries.json"),
+                    dry_run: true,
+                    changes: vec![ImportChange {
+                        key: String::from("host"),
+                        value: String::from("localhost"),
+                        change: ImportChangeKind::Overwritten,
+                    }],
+                },
+                r#"{"status":"success","operation":"import","data":{"source":"entries.json","dry_run":true,"changes":[{"key":"host","value":"localhost","change":"overwritten"}]}}"#,
+                "host\tlocalhost\toverwritten",
+            ),
+            (
+                CustomSuccessOperation::Run {
+                    command: vec![String::from("psql"), String::from("-h")],
+                    exit_code: 3,
+                },
+                r#"{"status":"success","operation":"run","data":{"command":["psql","-h"],"exit_code":3}}"#,
+                "3\tpsql\t-h",
+            ),
+            (
+                CustomSuccessOperation::Env {
+                    shell: Shell::Fish,
+                    variables: vec![EnvironmentVariable {
+                        name: String::from("DB_HOST"),
+                        key: String::from("db:host"),
+                        value: String::from("localhost"),
+                    }],
+                },
+                r#"{"status":"success","operation":"env","data":{"shell":"fish","variables":[{"name":"DB_HOST","key":"db:host","value":"localhost"}]}}"#,
+                "DB_HOST\tdb:host\tlocalhost",
+            ),
+            (
+                CustomSuccessOperation::Render {
+                    source: String::from("-"),
+                    contents: String::from("host=localhost\nport={{ port }}\n"),
+                    missing: vec![String::from("port"), String::from("user")],
+                },
+                r#"{"status":"success","operation":"render","data":{"source":"-","contents":"host=localhost\nport={{ port }}\n","missing":["port","user"]}}"#,
+                "-\thost=localhost\\nport={{ port }}\\n\tport,user",
+            ),
+            (
+                CustomSuccessOperation::Config {
+                    settings: vec![setting()],
+                },
+                r#"{"status":"success","operation":"config","data":{"settings":[{"name":"output","value":"json","scope":"user"}]}}"#,
+                "output\tjson\tuser",
+            ),
+            (
+                CustomSuccessOperation::ConfigGet { setting: setting() },
+                r#"{"status":"success","operation":"config_get","data":{"name":"output","value":"json","scope":"user"}}"#,
+                "output\tjson\tuser",
+            ),
+            (
+                CustomSuccessOperation::ConfigSet {
+                    name: String::from("output"),
+                    value: String::from("json"),
+                    path: String::from("/work/.reis.toml"),
+                    scope: ConfigScope::Project,
+                },
+                r#"{"status":"success","operation":"config_set","data":{"name":"output","value":"json","path":"/work/.reis.toml","scope":"project"}}"#,
+                "output\tjson\t/work/.reis.toml\tproject",
+            ),
+            (
+                CustomSuccessOperation::Init {
+                    path: String::from("/work/.reis/reis.db"),
+                    source: LocationSource::Local,
+                },
+                r#"{"status":"success","operation":"init","data":{"path":"/work/.reis/reis.db","source":"local"}}"#,
+                "/work/.reis/reis.db\tlocal",
+            ),
+            (
+                CustomSuccessOperation::Which {
+                    path: String::from("reis.db"),
+                    source: LocationSource::Explicit,
+                },
+                r#"{"status":"success","operation":"which","data":{"path":"reis.db","source":"explicit"}}"#,
+                "reis.db\texplicit",
+            ),
+            (
+                CustomSuccessOperation::Encrypt {
+                    path: String::from("reis.db"),
+                },
+                r#"{"status":"success","operation":"encrypt","data":{"path":"reis.db"}}"#,
+                "reis.db",
+            ),
+            (
+                CustomSuccessOperation::Decrypt {
+                    path: String::from("reis.db"),
+                },
+                r#"{"status":"success","operation":"decrypt","data":{"path":"reis.db"}}"#,
+                "reis.db",
+            ),
+            (
+                CustomSuccessOperation::Rekey {
+                    path: String::from("reis.db"),
+                },
+                r#"{"status":"success","operation":"rekey","data":{"path":"reis.db"}}"#,
+                "reis.db",
+            ),
+            (
+                CustomSuccessOperation::repair(Some((2, String::from("reis.db.5.quarantine")))),
+                r#"{"status":"success","operation":"repair","data":{"quarantined":2,"quarantine_path":"reis.db.5.quarantine"}}"#,
+                "2\treis.db.5.quarantine",
+            ),
+            (
+                CustomSuccessOperation::repair(None),
+                r#"{"status":"success","operation":"repair","data":{"quarantined":0,"quarantine_path":null}}"#,
+                "0\t",
+            ),
+        ]
+    }
+
+    #[test]
+    fn successes_are_reported_as_json() {
+        for (success, json, _) in successes() {
+            assert_eq!(success_to_json(&success), json, "{}", success.kind());
+        }
+    }
+
+    #[test]
+    fn successes_are_reported_as_tsv() {
+        for (success, _, tsv) in successes() {
+            assert_eq!(success_to_tsv(&success), tsv, "{}", success.kind());
+        }
+    }
+
+    #[test]
+    fn warnings_are_reported_as_json() {
+        let secret = ReisbaseEntry {
+            secret: true,
+            ..ReisbaseEntry::new("hunter2")
+        };
+        let warnings = [
+            (
+                CustomReisActionWarning::EmptyDatabase,
+                r#"{"status":"warning","warning":"empty_database","message":"Database doesn't contain any value!","data":null}"#,
+            ),
+            (
+                CustomReisActionWarning::SettingNotSet {
+                    setting: String::from("output"),
+                },
+                r#"{"status":"warning","warning":"setting_not_set","message":"The setting output is not configured! You can set it with the command: config-set output value","data":{"setting":"output"}}"#,
+            ),
+            (
+                CustomReisActionWarning::entry_already_exists("pw", &secret, "hunter3", &[]),
+                r#"{"status":"warning","warning":"entry_already_exists","message":"The key pw already exists in this database, with the value of ********.","data":{"key":"pw","old_value":"********","new_value":"********"}}"#,
+            ),
+            (
+                CustomReisActionWarning::entry_doesnt_exists(
+                    &ReisbaseAction::Get {
+                        key: String::from("hots"),
+                        arguments: vec![],
+                    },
+                    "hots",
+                    None,
+                    vec![String::from("host")],
+                ),
+                r#"{"status":"warning","warning":"entry_doesnt_exist","message":"The entry hots does not exists! You can create a new one with the command: set hots value","data":{"key":"hots","value":null,"action":"get","suggestions":["host"]}}"#,
+            ),
+            (
+                CustomReisActionWarning::ImportConflicts {
+                    source: String::from("entries.json"),
+                    imported: 1,
+                    conflicts: vec![CustomReisActionWarning::entry_already_exists(
+                        "host",
+                        &ReisbaseEntry::new("localhost"),
+                        "127.0.0.1",
+                        &[],
+                    )],
+                    prompt: false,
+                },
+                r#"{"status":"warning","warning":"import_conflicts","message":"Nothing was imported from entries.json, since some of its keys already exist in this database: host! You can choose what to do with them with --on-conflict skip, overwrite or prompt.","data":{"source":"entries.json","imported":1,"conflicts":[{"key":"host","old_value":"localhost","new_value":"127.0.0.1"}]}}"#,
+            ),
+            (
+                CustomReisActionWarning::SecretsNotRevealed {
+                    keys: vec![String::from("pw")],
+                },
+                r#"{"status":"warning","warning":"secrets_not_revealed","message":"The keys pw are secret, so their values are only written with --reveal!","data":{"keys":["pw"]}}"#,
+            ),
+            (
+                CustomReisActionWarning::history_version_doesnt_exists("host", 3),
+                r#"{"status":"warning","warning":"history_version_doesnt_exist","message":"The key host has no version 3! You can list its versions with the command: history host","data":{"key":"host","version":3}}"#,
+            ),
+            (
+                CustomReisActionWarning::clear_without_force(&[ReisbaseActionsArguments::Force]),
+                r#"{"status":"warning","warning":"required_arguments_not_specified","message":"The Clear action requires the -f argument!","data":{"operation":{"action":"clr","arguments":["-f"]}}}"#,
+            ),
+        ];
+        for (warning, json) in warnings {
+            assert_eq!(warning_to_json(&warning), json, "{}", warning.kind());
+        }
+    }
+
+    #[test]
+    fn errors_are_reported_as_json_and_tsv() {
+        let error = CustomReisIOFailure::missing_argument_value("--ttl");
+        assert_eq!(
+            error_to_json(&error),
+            r#"{"status":"error","error":"invalid_input","message":"The argument --ttl requires a value!","details":"--ttl"}"#
+        );
+        assert_eq!(
+            error_to_tsv(&error),
+            "error\tinvalid_input\tThe argument --ttl requires a value!"
+        );
+    }
+
+    #[test]
+    fn warnings_are_reported_as_tsv() {
+        assert_eq!(
+            warning_to_tsv(&CustomReisActionWarning::EmptyDatabase),
+            "warning\tempty_database\tDatabase doesn't contain any value!"
+        );
+    }
+}
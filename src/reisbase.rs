@@ -1,71 +1,452 @@
-use crate::constants::DatabaseStringConstants;
+use crate::constants::HISTORY_LIMIT;
+use crate::database_format::{self, CorruptLine};
+use crate::encryption::{self, Encryption};
+use crate::entry::{HistoryVersion, KeyedEntry, ReisbaseEntry, UndoRecord};
 use crate::error_handler::ErrorHandler;
 use crate::failures::CustomReisIOFailure;
-use std::collections::HashMap;
+use crate::namespace::{self, NamespaceScope, NamespaceSummary};
+use crate::search::{SearchMatch, SearchQuery};
+use crate::storage;
+use crate::suggestions;
+use crate::time;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::io::ErrorKind;
 use std::{fs, io};
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct Reisbase {
-    entries: HashMap<String, String>,
-}
-
-impl Drop for Reisbase {
-    fn drop(&mut self) {
-        let contents = self
-            .entries
-            .iter()
-            .map(|(key, value)| format_entry(key, value))
-            .collect::<String>();
-
-        _ = fs::write(DatabaseStringConstants::DATABASE_NAME, contents);
-    }
+    path: String,
+    /// Every namespace in the database, including the ones without entries.
+    namespaces: BTreeSet<String>,
+    entries: HashMap<String, ReisbaseEntry>,
+    history: HashMap<String, Vec<HistoryVersion>>,
+    undo_journal: Vec<UndoRecord>,
+    corrupt_lines: Vec<CorruptLine>,
+    /// The key the database is encrypted with when saved, if any.
+    encryption: Option<Encryption>,
+    /// The key the file was encrypted with when it was read or last saved.
+    stored_encryption: Option<Encryption>,
+    dirty: bool,
+    /// Whether the undo journal already describes a change made by this instance, instead of
+    /// the one loaded from the file.
+    journal_started: bool,
 }
 
 impl Reisbase {
+    /// Reads the database at `db_name`, creating it if it doesn't exist. Fails if any line of
+    /// the file can't be read, so a corrupt database is never overwritten.
     pub fn build(db_name: &str) -> Result<Reisbase, CustomReisIOFailure> {
-        read_database_contents(db_name)
+        let database = Reisbase::build_for_repair(db_name)?;
+        if database.corrupt_lines.is_empty() {
+            Ok(database)
+        } else {
+            Err(CustomReisIOFailure::corrupted_database(
+                db_name,
+                &database.corrupt_lines,
+            ))
+        }
+    }
+
+    /// Reads the database at `db_name` keeping aside the lines that can't be read, so the
+    /// valid entries can be salvaged with [`Reisbase::repair`].
+    pub fn build_for_repair(db_name: &str) -> Result<Reisbase, CustomReisIOFailure> {
+        let contents = read_database_contents(db_name)
             .or_else(|err| handle_database_init_failure(err, db_name))
-            .map_err(ErrorHandler::handle_io_error)
-            .and_then(db_file_to_entries)
+            .map_err(ErrorHandler::handle_io_error)?;
+        if !encryption::is_encrypted(&contents) {
+            return db_file_to_entries(contents, db_name, None);
+        }
+
+        let passphrase = encryption::passphrase(db_name)?;
+        let (contents, encryption) = encryption::decrypt(db_name, &contents, &passphrase)?;
+        db_file_to_entries(contents, db_name, Some(encryption))
+    }
+
+    /// An empty database for the actions that only work with its location, which is never
+    /// read nor written.
+    pub fn detached(db_name: &str) -> Reisbase {
+        db_file_to_entries(String::new(), db_name, None).expect("empty contents have no version")
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.encryption.is_some()
+    }
+
+    /// Changes the key the database is encrypted with from the next save on, or stores it as
+    /// plain text if `encryption` is [`None`].
+    pub fn set_encryption(&mut self, encryption: Option<Encryption>) {
+        self.encryption = encryption;
+        self.dirty = true;
+    }
+
+    /// Writes the entries back to the database file. Does nothing if no entry was changed
+    /// since the database was built or last saved.
+    ///
+    /// When the database is encrypted with a new key, the previous file isn't kept as the
+    /// rollback backup, since it could be read without that key.
+    pub fn save(&mut self) -> Result<(), CustomReisIOFailure> {
+        if !self.dirty {
+            return Ok(());
+        }
+        if !self.corrupt_lines.is_empty() {
+            return Err(CustomReisIOFailure::corrupted_database(
+                &self.path,
+                &self.corrupt_lines,
+            ));
+        }
+
+        let now = time::now();
+        self.entries.retain(|_, entry| !entry.is_expired(now));
+        let mut contents = database_format::serialize(
+            &self.namespaces,
+            &self.entries,
+            &self.history,
+            &self.undo_journal,
+        );
+        if let Some(encryption) = &self.encryption {
+            contents = encryption.encrypt(&contents)?;
+        }
+
+        let rekeyed = self.encryption.is_some() && self.encryption != self.stored_encryption;
+        if rekeyed {
+            storage::remove_backup(&self.path)
+        } else {
+            storage::create_backup(&self.path)
+        }
+        .and_then(|_| storage::write_atomically(&self.path, contents.as_bytes()))
+        .map_err(ErrorHandler::handle_io_error)?;
+        self.stored_encryption = self.encryption.clone();
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// Replaces the entries with the ones kept in the rollback backup. The current contents
+    /// become the new backup once the database is saved.
+    pub fn restore_backup(&mut self) -> Result<String, CustomReisIOFailure> {
+        let backup_path = storage::backup_path(&self.path);
+        let mut contents = read_database_contents(&backup_path)
+            .map_err(|error| CustomReisIOFailure::backup_not_found(&backup_path, error))?;
+        if encryption::is_encrypted(&contents) {
+            let passphrase = match &self.encryption {
+                Some(encryption) => encryption.passphrase().to_owned(),
+                None => encryption::passphrase(&backup_path)?,
+            };
+            contents = encryption::decrypt(&backup_path, &contents, &passphrase)?.0;
+        }
+
+        let backup = database_format::deserialize(&contents).map_err(|unsupported| {
+            CustomReisIOFailure::unsupported_database_version(&backup_path, &unsupported.0)
+        })?;
+        if !backup.corrupt_lines.is_empty() {
+            return Err(CustomReisIOFailure::corrupted_database(
+                &backup_path,
+                &backup.corrupt_lines,
+            ));
+        }
+
+        self.namespaces = backup.namespaces;
+        let mut previous_entries = std::mem::replace(&mut self.entries, backup.entries);
+        let keys = previous_entries
+            .keys()
+            .chain(self.entries.keys())
+            .cloned()
+            .collect::<HashSet<String>>();
+        for key in keys {
+            let previous = previous_entries.remove(&key);
+            if previous.as_ref() != self.entries.get(&key) {
+                self.record_change(&key, previous);
+            }
+        }
+        self.dirty = true;
+        Ok(backup_path)
+    }
+
+    /// Moves the lines that couldn't be read into a new quarantine file next to the database,
+    /// leaving only the valid entries to be saved. Earlier quarantine files are kept. Returns
+    /// the number of quarantined lines and the quarantine file path, or [`None`] if there was
+    /// nothing to repair.
+    pub fn repair(&mut self) -> Result<Option<(usize, String)>, CustomReisIOFailure> {
+        if self.corrupt_lines.is_empty() {
+            return Ok(None);
+        }
+
+        let mut contents = database_format::serialize_quarantine(&self.corrupt_lines);
+        if let Some(encryption) = &self.encryption {
+            contents = encryption.encrypt(&contents)?;
+        }
+        let quarantine_path =
+            storage::write_quarantine(&self.path, time::now(), contents.as_bytes())
+                .map_err(ErrorHandler::handle_io_error)?;
+
+        let quarantined = self.corrupt_lines.len();
+        self.corrupt_lines.clear();
+        self.dirty = true;
+        Ok(Some((quarantined, quarantine_path)))
     }
 
-    pub fn insert(&mut self, key: &str, value: &str) {
-        self.entries.insert(key.to_string(), value.to_string());
+    /// Inserts `entry` under `key`. The key is only in the namespace it is written in if that
+    /// namespace is already declared.
+    pub fn insert(&mut self, key: &str, entry: ReisbaseEntry) {
+        let previous = self.entries.insert(key.to_string(), entry);
+        self.record_change(key, previous);
     }
 
     pub fn get(&mut self, key: &str) -> Option<String> {
-        self.entries.get(key).map(|value| value.to_owned())
+        self.get_entry(key).map(|entry| entry.value.to_owned())
+    }
+
+    /// Returns the entry for `key`, unless it doesn't exist or has expired.
+    pub fn get_entry(&self, key: &str) -> Option<&ReisbaseEntry> {
+        self.entries
+            .get(key)
+            .filter(|entry| !entry.is_expired(time::now()))
+    }
+
+    pub fn delete(&mut self, key: &str) -> Option<ReisbaseEntry> {
+        let removed = self.entries.remove(key);
+        if removed.is_some() {
+            self.record_change(key, removed.clone());
+        }
+        removed.filter(|entry| !entry.is_expired(time::now()))
+    }
+
+    pub fn count(&self, scope: &NamespaceScope) -> usize {
+        self.live_entries()
+            .filter(|(key, _)| scope.contains(self.namespace_of(key)))
+            .count()
+    }
+
+    /// Returns every entry in `scope`, sorted by key.
+    pub fn get_all(&self, scope: &NamespaceScope) -> Vec<KeyedEntry> {
+        self.sorted_entries(scope, |_| true)
+    }
+
+    /// Same as [`Reisbase::get_all`], but only with the entries whose description contains
+    /// `term`, ignoring case.
+    pub fn search_descriptions(&self, term: &str, scope: &NamespaceScope) -> Vec<KeyedEntry> {
+        self.sorted_entries(scope, |entry| entry.description_contains(term))
     }
 
-    pub fn delete(&mut self, key: &str) -> Option<String> {
-        self.entries.remove(key)
+    /// Returns the existing keys that are likely typos of `key`, from the closest.
+    pub fn similar_keys(&self, key: &str) -> Vec<String> {
+        suggestions::closest(key, self.live_entries().map(|(key, _)| key.as_str()))
+            .into_iter()
+            .map(String::from)
+            .collect()
     }
 
-    pub fn count(&self) -> usize {
-        self.entries.len()
+    /// Returns the entries in `scope` matched by `query`, sorted by key.
+    pub fn find(&self, query: &SearchQuery, scope: &NamespaceScope) -> Vec<SearchMatch> {
+        let mut matches = self
+            .live_entries()
+            .filter(|(key, _)| scope.contains(self.namespace_of(key)))
+            .filter_map(|(key, entry)| query.matches(key, entry))
+            .collect::<Vec<SearchMatch>>();
+        matches.sort_by(|a, b| a.key.cmp(&b.key));
+        matches
     }
 
-    pub fn get_all(&self) -> Option<String> {
-        let entries = self
+    /// Deletes every entry in `scope`, returning how many of them hadn't expired yet.
+    pub fn clear(&mut self, scope: &NamespaceScope) -> usize {
+        let count = self.count(scope);
+        let keys = self
             .entries
+            .keys()
+            .filter(|key| scope.contains(self.namespace_of(key)))
+            .cloned()
+            .collect::<Vec<String>>();
+        for key in keys {
+            let entry = self.entries.remove(&key);
+            self.record_change(&key, entry);
+        }
+        count
+    }
+
+    /// Returns every namespace along with how many entries it holds, sorted by name.
+    pub fn namespaces(&self) -> Vec<NamespaceSummary> {
+        self.namespaces
             .iter()
-            .map(|(key, value)| format_entry(key, value))
-            .collect::<String>();
+            .map(|namespace| NamespaceSummary {
+                name: namespace.clone(),
+                count: self.count(&NamespaceScope::Named(namespace.clone())),
+            })
+            .collect()
+    }
+
+    pub fn has_namespace(&self, namespace: &str) -> bool {
+        self.namespaces.contains(namespace)
+    }
 
-        string_to_option(entries)
+    /// Adds an empty namespace. Returns false if it already existed.
+    pub fn create_namespace(&mut self, namespace: &str) -> bool {
+        let created = self.namespaces.insert(namespace.to_owned());
+        self.dirty |= created;
+        created
     }
 
-    pub fn clear(&mut self) {
-        self.entries.clear()
+    /// Moves every entry of the namespace `from` to the namespace `to`, which replaces it.
+    /// The previous versions of the entries stay under their old keys. Returns how many
+    /// entries were moved.
+    pub fn rename_namespace(&mut self, from: &str, to: &str) -> usize {
+        let scope = NamespaceScope::Named(from.to_owned());
+        let moved = self.count(&scope);
+        let keys = self
+            .entries
+            .keys()
+            .filter(|key| scope.contains(self.namespace_of(key)))
+            .cloned()
+            .collect::<Vec<String>>();
+        for key in keys {
+            let Some(entry) = self.entries.remove(&key) else {
+                continue;
+            };
+            let new_key = namespace::rename(&key, to);
+            self.record_change(&key, Some(entry.clone()));
+            let previous = self.entries.insert(new_key.clone(), entry);
+            self.record_change(&new_key, previous);
+        }
+        self.namespaces.remove(from);
+        self.namespaces.insert(to.to_owned());
+        self.dirty = true;
+        moved
+    }
+
+    /// Deletes the namespace along with its entries, returning how many of them hadn't
+    /// expired yet.
+    pub fn drop_namespace(&mut self, namespace: &str) -> usize {
+        let deleted = self.clear(&NamespaceScope::Named(namespace.to_owned()));
+        self.namespaces.remove(namespace);
+        self.dirty = true;
+        deleted
+    }
+
+    /// Returns the previous versions of `key`, from the most recent to the oldest.
+    pub fn history(&self, key: &str) -> Vec<&HistoryVersion> {
+        self.history
+            .get(key)
+            .map(|versions| versions.iter().rev().collect())
+            .unwrap_or_default()
+    }
+
+    /// Makes the previous `version` of `key` its current entry, where `1` is the most recent
+    /// previous version. Returns the restored entry, or [`None`] if there is no such version.
+    pub fn revert(&mut self, key: &str, version: usize) -> Option<ReisbaseEntry> {
+        let mut entry = self
+            .history(key)
+            .get(version.checked_sub(1)?)
+            .map(|version| version.entry.clone())?;
+        if entry.is_expired(time::now()) {
+            entry.expires_at = None;
+        }
+        self.insert(key, entry.clone());
+        Some(entry)
+    }
+
+    /// Reverses the last change made to the database. Undoing again redoes the change.
+    /// Returns the number of entries that were restored, or [`None`] if there was nothing
+    /// to undo.
+    pub fn undo(&mut self) -> Option<usize> {
+        if self.journal_started || self.undo_journal.is_empty() {
+            return None;
+        }
+
+        let journal = std::mem::take(&mut self.undo_journal);
+        let restored = journal.len();
+        for record in journal {
+            let current = match record.previous {
+                Some(entry) => {
+                    if record.namespaced {
+                        self.declare_namespace_of(&record.key);
+                    }
+                    self.entries.insert(record.key.clone(), entry)
+                }
+                None => self.entries.remove(&record.key),
+            };
+            self.record_change(&record.key, current);
+        }
+        Some(restored)
     }
 
     pub fn exists(&self, key: &str) -> bool {
-        self.entries.contains_key(key)
+        self.get_entry(key).is_some()
+    }
+
+    pub fn is_empty(&self, scope: &NamespaceScope) -> bool {
+        self.count(scope) == 0
+    }
+
+    /// Returns the namespace `key` is in, or [`None`] if it is in the default one, which is
+    /// also the case when the namespace it is written in isn't declared.
+    fn namespace_of<'k>(&self, key: &'k str) -> Option<&'k str> {
+        namespace::namespace_of(key).filter(|namespace| self.namespaces.contains(*namespace))
+    }
+
+    /// Adds the namespace `key` is written in, if it is written in one that didn't exist yet.
+    /// Only undoing a change declares namespaces this way, for the keys that were in one.
+    fn declare_namespace_of(&mut self, key: &str) {
+        if let Some(namespace) = namespace::namespace_of(key) {
+            if !self.namespaces.contains(namespace) {
+                self.namespaces.insert(namespace.to_owned());
+            }
+        }
+    }
+
+    /// Keeps what `key` held before a change, both in its history and in the undo journal.
+    fn record_change(&mut self, key: &str, previous: Option<ReisbaseEntry>) {
+        let now = time::now();
+        let previous = previous.filter(|entry| !entry.is_expired(now));
+
+        if !self.journal_started {
+            self.undo_journal.clear();
+            self.journal_started = true;
+        }
+        if !self.undo_journal.iter().any(|record| record.key == key) {
+            self.undo_journal.push(UndoRecord {
+                key: key.to_owned(),
+                previous: previous.clone(),
+                namespaced: self.namespace_of(key).is_some(),
+            });
+        }
+
+        if let Some(entry) = previous {
+            let versions = self.history.entry(key.to_owned()).or_default();
+            versions.push(HistoryVersion {
+                entry,
+                replaced_at: now,
+            });
+            if versions.len() > HISTORY_LIMIT {
+                versions.drain(..versions.len() - HISTORY_LIMIT);
+            }
+        }
+        self.dirty = true;
     }
 
-    pub fn is_empty(&self) -> bool {
-        self.entries.is_empty()
+    fn sorted_entries<P>(&self, scope: &NamespaceScope, predicate: P) -> Vec<KeyedEntry>
+    where
+        P: Fn(&ReisbaseEntry) -> bool,
+    {
+        let mut entries = self
+            .live_entries()
+            .filter(|(key, entry)| scope.contains(self.namespace_of(key)) && predicate(entry))
+            .map(|(key, entry)| KeyedEntry {
+                key: key.to_owned(),
+                entry: entry.clone(),
+            })
+            .collect::<Vec<KeyedEntry>>();
+        entries.sort_by(|a, b| a.key.cmp(&b.key));
+        entries
+    }
+
+    /// Iterates over the entries that haven't expired yet.
+    fn live_entries(&self) -> impl Iterator<Item = (&String, &ReisbaseEntry)> {
+        let now = time::now();
+        self.entries
+            .iter()
+            .filter(move |(_, entry)| !entry.is_expired(now))
     }
 }
 
@@ -74,7 +455,8 @@ fn read_database_contents(name: &str) -> io::Result<String> {
 }
 
 fn create_database_file(db_name: &str) -> io::Result<String> {
-    fs::write(db_name, "").map(|_| String::new())
+    storage::create_database(db_name)?;
+    read_database_contents(db_name)
 }
 
 fn handle_database_init_failure(error: io::Error, db_name: &str) -> io::Result<String> {
@@ -85,35 +467,24 @@ fn handle_database_init_failure(error: io::Error, db_name: &str) -> io::Result<S
     }
 }
 
-fn db_file_to_entries(contents: String) -> Result<Reisbase, CustomReisIOFailure> {
-    let entries_iter = contents
-        .lines()
-        .filter_map(|line| line.split_once(DatabaseStringConstants::ENTRIES_SEPARATOR))
-        .map(|(key, value)| (remove_key_identifier(key), value.to_owned()))
-        .collect::<Vec<(String, String)>>();
-
-    let entries = HashMap::from_iter(entries_iter);
-    Ok(Reisbase { entries })
-}
-
-fn format_entry(key: &str, value: &str) -> String {
-    format!(
-        "{}{}{}{}\n",
-        DatabaseStringConstants::KEY_IDENTIFIER,
-        key,
-        DatabaseStringConstants::ENTRIES_SEPARATOR,
-        value
-    )
-}
-
-fn remove_key_identifier(key: &str) -> String {
-    key.replacen(DatabaseStringConstants::KEY_IDENTIFIER, "", 1)
-}
-
-fn string_to_option(value: String) -> Option<String> {
-    if value.is_empty() {
-        None
-    } else {
-        Some(value)
-    }
+fn db_file_to_entries(
+    contents: String,
+    db_name: &str,
+    encryption: Option<Encryption>,
+) -> Result<Reisbase, CustomReisIOFailure> {
+    let parsed = database_format::deserialize(&contents).map_err(|unsupported| {
+        CustomReisIOFailure::unsupported_database_version(db_name, &unsupported.0)
+    })?;
+    Ok(Reisbase {
+        path: db_name.to_owned(),
+        namespaces: parsed.namespaces,
+        entries: parsed.entries,
+        history: parsed.history,
+        undo_journal: parsed.undo_journal,
+        corrupt_lines: parsed.corrupt_lines,
+        stored_encryption: encryption.clone(),
+        encryption,
+        dirty: false,
+        journal_started: false,
+    })
 }
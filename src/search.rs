@@ -0,0 +1,200 @@
+use std::ops::Range;
+
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
+
+use crate::{constants::DatabaseStringConstants, entry::ReisbaseEntry};
+
+const HIGHLIGHT_START: &str = "\x1b[1;31m";
+const HIGHLIGHT_END: &str = "\x1b[0m";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SearchMode {
+    /// Matches anywhere in the text.
+    #[default]
+    Contains,
+    /// Matches at the start of the text.
+    Prefix,
+    /// Matches the whole text, where `*` matches any run of characters and `?` any one.
+    Glob,
+    /// Matches a regular expression anywhere in the text.
+    Regex,
+}
+
+impl SearchMode {
+    pub fn new(mode: &str) -> Option<SearchMode> {
+        match mode {
+            "contains" => Some(SearchMode::Contains),
+            "prefix" => Some(SearchMode::Prefix),
+            "glob" => Some(SearchMode::Glob),
+            "regex" => Some(SearchMode::Regex),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for SearchMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SearchMode::Contains => write!(f, "contains"),
+            SearchMode::Prefix => write!(f, "prefix"),
+            SearchMode::Glob => write!(f, "glob"),
+            SearchMode::Regex => write!(f, "regex"),
+        }
+    }
+}
+
+/// Which parts of an entry a search looks at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchFields {
+    pub keys: bool,
+    pub values: bool,
+    pub descriptions: bool,
+}
+
+impl SearchFields {
+    pub fn all() -> SearchFields {
+        SearchFields {
+            keys: true,
+            values: true,
+            descriptions: true,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SearchQuery {
+    regex: Regex,
+    fields: SearchFields,
+}
+
+impl SearchQuery {
+    pub fn new(
+        pattern: &str,
+        mode: SearchMode,
+        fields: SearchFields,
+        case_insensitive: bool,
+    ) -> Result<SearchQuery, regex::Error> {
+        let expression = match mode {
+            SearchMode::Contains => regex::escape(pattern),
+            SearchMode::Prefix => format!("^{}", regex::escape(pattern)),
+            SearchMode::Glob => glob_to_regex(pattern),
+            SearchMode::Regex => pattern.to_owned(),
+        };
+        let regex = RegexBuilder::new(&expression)
+            .case_insensitive(case_insensitive)
+            .dot_matches_new_line(mode == SearchMode::Glob)
+            .build()?;
+        Ok(SearchQuery { regex, fields })
+    }
+
+    /// Returns the entry along with where it matched, or [`None`] if it didn't match. The
+    /// values of secret entries are never searched, so a match can't reveal their contents.
+    pub fn matches(&self, key: &str, entry: &ReisbaseEntry) -> Option<SearchMatch> {
+        let entry = entry.masked();
+        let key_matches = self.find_in(self.fields.keys, key);
+        let value_matches = self.find_in(self.fields.values && !entry.secret, &entry.value);
+        let description_matches = entry
+            .description
+            .as_deref()
+            .map(|description| self.find_in(self.fields.descriptions, description))
+            .unwrap_or_default();
+
+        if key_matches.is_empty() && value_matches.is_empty() && description_matches.is_empty() {
+            return None;
+        }
+        Some(SearchMatch {
+            key: key.to_owned(),
+            value: entry.value,
+            description: entry.description,
+            key_matches,
+            value_matches,
+            description_matches,
+        })
+    }
+
+    fn find_in(&self, enabled: bool, text: &str) -> Vec<Range<usize>> {
+        if !enabled {
+            return Vec::new();
+        }
+        self.regex
+            .find_iter(text)
+            .map(|found| found.range())
+            .filter(|range| !range.is_empty())
+            .collect()
+    }
+}
+
+/// An entry found by a search, with the byte ranges that matched in each of its parts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SearchMatch {
+    pub key: String,
+    pub value: String,
+    pub description: Option<String>,
+    pub key_matches: Vec<Range<usize>>,
+    pub value_matches: Vec<Range<usize>>,
+    pub description_matches: Vec<Range<usize>>,
+}
+
+impl SearchMatch {
+    /// Formats the match the same way entries are listed by Get All, optionally highlighting
+    /// the parts that matched.
+    pub fn format(&self, highlighted: bool) -> String {
+        let format_part = |text: &str, matches: &[Range<usize>]| {
+            if highlighted {
+                highlight(text, matches)
+            } else {
+                text.to_owned()
+            }
+        };
+        let description = self
+            .description
+            .as_deref()
+            .map(|description| {
+                format!(
+                    "{}{}{}",
+                    DatabaseStringConstants::ENTRIES_SEPARATOR,
+                    DatabaseStringConstants::DESCRIPTION_IDENTIFIER,
+                    format_part(description, &self.description_matches)
+                )
+            })
+            .unwrap_or_default();
+
+        format!(
+            "{}{}{}{}{}\n",
+            DatabaseStringConstants::KEY_IDENTIFIER,
+            format_part(&self.key, &self.key_matches),
+            DatabaseStringConstants::ENTRIES_SEPARATOR,
+            format_part(&self.value, &self.value_matches),
+            description
+        )
+    }
+}
+
+/// Wraps the `matches` ranges of `text` in terminal highlighting escapes.
+pub fn highlight(text: &str, matches: &[Range<usize>]) -> String {
+    let mut highlighted = String::with_capacity(text.len());
+    let mut last = 0;
+    for range in matches {
+        highlighted.push_str(&text[last..range.start]);
+        highlighted.push_str(HIGHLIGHT_START);
+        highlighted.push_str(&text[range.clone()]);
+        highlighted.push_str(HIGHLIGHT_END);
+        last = range.end;
+    }
+    highlighted.push_str(&text[last..]);
+    highlighted
+}
+
+fn glob_to_regex(glob: &str) -> String {
+    let mut expression = String::from("^");
+    for character in glob.chars() {
+        match character {
+            '*' => expression.push_str(".*"),
+            '?' => expression.push('.'),
+            _ => expression.push_str(&regex::escape(&character.to_string())),
+        }
+    }
+    expression.push('$');
+    expression
+}
@@ -0,0 +1,162 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+use crate::{constants::DatabaseStringConstants, database_format};
+
+/// The mode new files are created with, since they may hold secrets: only their owner can
+/// read or write them.
+#[cfg(unix)]
+const PRIVATE_FILE_MODE: u32 = 0o600;
+
+/// Replaces the contents of `path` without ever leaving it partially written: the contents
+/// go to a sibling temporary file, which is synced and then renamed over the original. The
+/// file keeps the permissions it had, or is only readable by its owner if it is new.
+pub fn write_atomically(path: &str, contents: &[u8]) -> io::Result<()> {
+    let temporary_path = temporary_path(path);
+    let permissions = fs::metadata(path)
+        .map(|metadata| metadata.permissions())
+        .ok();
+    let result = write_and_sync(&temporary_path, contents, permissions)
+        .and_then(|_| fs::rename(&temporary_path, path))
+        .and_then(|_| sync_parent_directory(path));
+
+    if result.is_err() {
+        _ = fs::remove_file(&temporary_path);
+    }
+    result
+}
+
+/// Keeps a copy of the current contents of `path` as its rollback backup. Does nothing if
+/// there is no file at `path` yet.
+pub fn create_backup(path: &str) -> io::Result<()> {
+    match fs::read(path) {
+        Ok(contents) => write_atomically(&backup_path(path), &contents),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(error) => Err(error),
+    }
+}
+
+/// Creates an empty database at `path`, along with its missing parent directories. Returns
+/// false if a file already exists at `path`, which is left untouched.
+pub fn create_database(path: &str) -> io::Result<bool> {
+    if let Some(parent) = Path::new(path).parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let file = private_options().write(true).create_new(true).open(path);
+    match file {
+        Ok(mut file) => {
+            writeln!(file, "{}", database_format::header())?;
+            file.sync_all()?;
+            Ok(true)
+        }
+        Err(error) if error.kind() == io::ErrorKind::AlreadyExists => Ok(false),
+        Err(error) => Err(error),
+    }
+}
+
+/// Removes the rollback backup of `path`, so an unencrypted copy of the database isn't left
+/// behind once it is encrypted.
+pub fn remove_backup(path: &str) -> io::Result<()> {
+    match fs::remove_file(backup_path(path)) {
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
+
+pub fn backup_path(path: &str) -> String {
+    format!("{}{}", path, DatabaseStringConstants::BACKUP_EXTENSION)
+}
+
+/// Writes `contents` to a new quarantine file next to `path`, named after `now` so the files
+/// of earlier repairs are never replaced. Returns the path of the file.
+pub fn write_quarantine(path: &str, now: u64, contents: &[u8]) -> io::Result<String> {
+    let mut attempt = 0;
+    loop {
+        let quarantine_path = match attempt {
+            0 => format!(
+                "{}.{}{}",
+                path,
+                now,
+                DatabaseStringConstants::QUARANTINE_EXTENSION
+            ),
+            _ => format!(
+                "{}.{}-{}{}",
+                path,
+                now,
+                attempt,
+                DatabaseStringConstants::QUARANTINE_EXTENSION
+            ),
+        };
+        match private_options()
+            .write(true)
+            .create_new(true)
+            .open(&quarantine_path)
+        {
+            Ok(mut file) => {
+                file.write_all(contents)?;
+                file.sync_all()?;
+                return Ok(quarantine_path);
+            }
+            Err(error) if error.kind() == io::ErrorKind::AlreadyExists => attempt += 1,
+            Err(error) => return Err(error),
+        }
+    }
+}
+
+fn temporary_path(path: &str) -> String {
+    format!(
+        "{}.{}{}",
+        path,
+        process::id(),
+        DatabaseStringConstants::TEMPORARY_EXTENSION
+    )
+}
+
+fn write_and_sync(
+    path: &str,
+    contents: &[u8],
+    permissions: Option<fs::Permissions>,
+) -> io::Result<()> {
+    let mut file = private_options()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)?;
+    if let Some(permissions) = permissions {
+        file.set_permissions(permissions)?;
+    }
+    file.write_all(contents)?;
+    file.sync_all()
+}
+
+/// Options that create files only readable by their owner, where the platform allows it.
+fn private_options() -> OpenOptions {
+    let mut options = OpenOptions::new();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(PRIVATE_FILE_MODE);
+    }
+    options
+}
+
+#[cfg(unix)]
+fn parent_directory(path: &str) -> PathBuf {
+    match Path::new(path).parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    }
+}
+
+#[cfg(unix)]
+fn sync_parent_directory(path: &str) -> io::Result<()> {
+    fs::File::open(parent_directory(path))?.sync_all()
+}
+
+/// Directories can't be opened as files on every platform, so there is nothing to sync.
+#[cfg(not(unix))]
+fn sync_parent_directory(_path: &str) -> io::Result<()> {
+    Ok(())
+}
@@ -1,44 +1,700 @@
-use crate::constants::SuccessfulOperationStrings;
+use serde::Serialize;
 
-#[derive(Debug)]
+use crate::{
+    config::{ConfigScope, ConfiguredSetting},
+    constants::{DatabaseStringConstants, ExitCodes, SuccessfulOperationStrings},
+    entry::{HistoryVersion, KeyedEntry, ReisbaseEntry},
+    environment::{self, EnvironmentVariable, Shell},
+    exchange::{ExchangeFormat, ImportChange, ImportChangeKind},
+    layers::LayeredEntry,
+    location::{DatabaseLocation, LocationSource},
+    namespace::NamespaceSummary,
+    search::SearchMatch,
+    time,
+};
+
+/// The result of an action that succeeded. Each variant keeps the data the action produced,
+/// which is either formatted as a message or serialized as the `data` of a report.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
 pub enum CustomSuccessOperation {
-    Insert(String),
-    Get(String),
-    Put(String),
-    Delete(String),
-    GetAll(String),
-    Clear(String),
+    Insert {
+        key: String,
+        value: String,
+    },
+    Get {
+        key: String,
+        #[serde(flatten)]
+        entry: ReisbaseEntry,
+        /// Whether the description was requested along with the value.
+        #[serde(skip)]
+        with_description: bool,
+    },
+    Explain {
+        key: String,
+        #[serde(flatten)]
+        entry: ReisbaseEntry,
+        /// The layer that answered.
+        #[serde(flatten)]
+        layer: DatabaseLocation,
+        /// The layers below it that also have the key, from the closest.
+        shadowed: Vec<DatabaseLocation>,
+    },
+    Put {
+        key: String,
+        value: String,
+        old_value: String,
+    },
+    Delete {
+        key: String,
+        old_value: String,
+    },
+    GetAll {
+        entries: Vec<KeyedEntry>,
+    },
+    GetMerged {
+        entries: Vec<LayeredEntry>,
+    },
+    Find {
+        matches: Vec<SearchMatch>,
+    },
+    Clear {
+        count: usize,
+    },
+    Ttl {
+        key: String,
+        expires_at: Option<u64>,
+        /// Seconds left until the entry expires.
+        remaining: Option<u64>,
+    },
+    History {
+        key: String,
+        current: Option<ReisbaseEntry>,
+        /// The previous versions, from the most recent to the oldest.
+        versions: Vec<HistoryVersion>,
+    },
+    Revert {
+        key: String,
+        version: usize,
+        #[serde(flatten)]
+        entry: ReisbaseEntry,
+    },
+    Undo {
+        restored: usize,
+    },
+    Namespaces {
+        namespaces: Vec<NamespaceSummary>,
+    },
+    CreateNamespace {
+        namespace: String,
+    },
+    RenameNamespace {
+        namespace: String,
+        new_namespace: String,
+        moved: usize,
+    },
+    DropNamespace {
+        namespace: String,
+        deleted: usize,
+    },
+    RestoreBackup {
+        backup_path: String,
+    },
+    Export {
+        format: ExchangeFormat,
+        count: usize,
+        contents: String,
+    },
+    Import {
+        source: String,
+        /// Whether the changes were only previewed, instead of written.
+        dry_run: bool,
+        changes: Vec<ImportChange>,
+    },
+    Run {
+        command: Vec<String>,
+        /// The status the command exited with, which reisbase exits with too.
+        exit_code: u8,
+    },
+    Env {
+        shell: Shell,
+        variables: Vec<EnvironmentVariable>,
+    },
+    Render {
+        source: String,
+        contents: String,
+        /// The keys of the template that don't exist, which were rendered with the default
+        /// value or kept as they were written.
+        missing: Vec<String>,
+    },
+    Config {
+        settings: Vec<ConfiguredSetting>,
+    },
+    ConfigGet {
+        #[serde(flatten)]
+        setting: ConfiguredSetting,
+    },
+    ConfigSet {
+        name: String,
+        value: String,
+        path: String,
+        scope: ConfigScope,
+    },
+    Init {
+        path: String,
+        source: LocationSource,
+    },
+    Which {
+        path: String,
+        source: LocationSource,
+    },
+    Encrypt {
+        path: String,
+    },
+    Decrypt {
+        path: String,
+    },
+    Rekey {
+        path: String,
+    },
+    Repair {
+        quarantined: usize,
+        quarantine_path: Option<String>,
+    },
 }
 
 impl CustomSuccessOperation {
-    pub fn insert(key: &str, new_value: &str) -> CustomSuccessOperation {
-        CustomSuccessOperation::Insert(SuccessfulOperationStrings::successful_insert_operation(
-            key, new_value,
-        ))
+    pub fn insert(key: &str, value: &str) -> CustomSuccessOperation {
+        CustomSuccessOperation::Insert {
+            key: String::from(key),
+            value: String::from(value),
+        }
+    }
+
+    pub fn get(key: &str, entry: &ReisbaseEntry, with_description: bool) -> CustomSuccessOperation {
+        CustomSuccessOperation::Get {
+            key: String::from(key),
+            entry: entry.clone(),
+            with_description,
+        }
+    }
+
+    pub fn put(key: &str, value: &str, old_value: &str) -> CustomSuccessOperation {
+        CustomSuccessOperation::Put {
+            key: String::from(key),
+            value: String::from(value),
+            old_value: String::from(old_value),
+        }
+    }
+
+    pub fn delete(key: &str, old_value: &str) -> CustomSuccessOperation {
+        CustomSuccessOperation::Delete {
+            key: String::from(key),
+            old_value: String::from(old_value),
+        }
+    }
+
+    pub fn clear(count: usize) -> CustomSuccessOperation {
+        CustomSuccessOperation::Clear { count }
+    }
+
+    pub fn ttl(key: &str, expires_at: Option<u64>, now: u64) -> CustomSuccessOperation {
+        CustomSuccessOperation::Ttl {
+            key: String::from(key),
+            expires_at,
+            remaining: expires_at.map(|expires_at| expires_at.saturating_sub(now)),
+        }
+    }
+
+    /// The history of `key`, with the values of secret versions masked.
+    pub fn history(
+        key: &str,
+        current: Option<&ReisbaseEntry>,
+        versions: &[&HistoryVersion],
+    ) -> CustomSuccessOperation {
+        CustomSuccessOperation::History {
+            key: String::from(key),
+            current: current.map(ReisbaseEntry::masked),
+            versions: versions
+                .iter()
+                .map(|version| HistoryVersion {
+                    entry: version.entry.masked(),
+                    replaced_at: version.replaced_at,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn revert(key: &str, version: usize, entry: ReisbaseEntry) -> CustomSuccessOperation {
+        CustomSuccessOperation::Revert {
+            key: String::from(key),
+            version,
+            entry,
+        }
     }
 
-    pub fn put(key: &str, value: &str) -> CustomSuccessOperation {
-        CustomSuccessOperation::Put(SuccessfulOperationStrings::successful_insert_operation(
-            key, value,
-        ))
+    pub fn undo(restored: usize) -> CustomSuccessOperation {
+        CustomSuccessOperation::Undo { restored }
     }
 
-    pub fn delete(key: &str) -> CustomSuccessOperation {
-        CustomSuccessOperation::Delete(SuccessfulOperationStrings::successful_delete_operation(key))
+    pub fn restore_backup(backup_path: &str) -> CustomSuccessOperation {
+        CustomSuccessOperation::RestoreBackup {
+            backup_path: String::from(backup_path),
+        }
     }
 
-    pub fn clear() -> CustomSuccessOperation {
-        CustomSuccessOperation::Clear(SuccessfulOperationStrings::successful_clear_operation())
+    pub fn repair(quarantined: Option<(usize, String)>) -> CustomSuccessOperation {
+        match quarantined {
+            Some((quarantined, quarantine_path)) => CustomSuccessOperation::Repair {
+                quarantined,
+                quarantine_path: Some(quarantine_path),
+            },
+            None => CustomSuccessOperation::Repair {
+                quarantined: 0,
+                quarantine_path: None,
+            },
+        }
     }
 
-    pub fn message(&self) -> &str {
+    /// The status reisbase exits with after this operation, which is the one of the command
+    /// for run, as documented in [`ExitCodes`].
+    pub fn exit_code(&self) -> u8 {
         match self {
-            CustomSuccessOperation::Insert(message) => message,
-            CustomSuccessOperation::Get(message) => message,
-            CustomSuccessOperation::Put(message) => message,
-            CustomSuccessOperation::Delete(message) => message,
-            CustomSuccessOperation::GetAll(message) => message,
-            CustomSuccessOperation::Clear(message) => message,
+            CustomSuccessOperation::Run { exit_code, .. } => *exit_code,
+            _ => ExitCodes::SUCCESS,
         }
     }
+
+    /// The name identifying this operation in structured output.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            CustomSuccessOperation::Insert { .. } => "set",
+            CustomSuccessOperation::Get { .. } => "get",
+            CustomSuccessOperation::Explain { .. } => "explain",
+            CustomSuccessOperation::Put { .. } => "put",
+            CustomSuccessOperation::Delete { .. } => "delete",
+            CustomSuccessOperation::GetAll { .. } => "get_all",
+            CustomSuccessOperation::GetMerged { .. } => "get_merged",
+            CustomSuccessOperation::Find { .. } => "find",
+            CustomSuccessOperation::Clear { .. } => "clear",
+            CustomSuccessOperation::Ttl { .. } => "ttl",
+            CustomSuccessOperation::History { .. } => "history",
+            CustomSuccessOperation::Revert { .. } => "revert",
+            CustomSuccessOperation::Undo { .. } => "undo",
+            CustomSuccessOperation::Namespaces { .. } => "namespaces",
+            CustomSuccessOperation::CreateNamespace { .. } => "create_namespace",
+            CustomSuccessOperation::RenameNamespace { .. } => "rename_namespace",
+            CustomSuccessOperation::DropNamespace { .. } => "drop_namespace",
+            CustomSuccessOperation::RestoreBackup { .. } => "restore_backup",
+            CustomSuccessOperation::Export { .. } => "export",
+            CustomSuccessOperation::Import { .. } => "import",
+            CustomSuccessOperation::Run { .. } => "run",
+            CustomSuccessOperation::Env { .. } => "env",
+            CustomSuccessOperation::Render { .. } => "render",
+            CustomSuccessOperation::Config { .. } => "config",
+            CustomSuccessOperation::ConfigGet { .. } => "config_get",
+            CustomSuccessOperation::ConfigSet { .. } => "config_set",
+            CustomSuccessOperation::Init { .. } => "init",
+            CustomSuccessOperation::Which { .. } => "which",
+            CustomSuccessOperation::Encrypt { .. } => "encrypt",
+            CustomSuccessOperation::Decrypt { .. } => "decrypt",
+            CustomSuccessOperation::Rekey { .. } => "rekey",
+            CustomSuccessOperation::Repair { .. } => "repair",
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            CustomSuccessOperation::Insert { key, value }
+            | CustomSuccessOperation::Put { key, value, .. } => {
+                SuccessfulOperationStrings::successful_insert_operation(key, value)
+            }
+            CustomSuccessOperation::Get {
+                entry,
+                with_description: true,
+                ..
+            } => SuccessfulOperationStrings::value_with_description(
+                &entry.value,
+                entry.description.as_deref(),
+            ),
+            CustomSuccessOperation::Get { entry, .. } => entry.value.to_owned(),
+            CustomSuccessOperation::Explain {
+                entry,
+                layer,
+                shadowed,
+                ..
+            } => SuccessfulOperationStrings::explained_value(&entry.value, layer, shadowed),
+            CustomSuccessOperation::Delete { key, .. } => {
+                SuccessfulOperationStrings::successful_delete_operation(key)
+            }
+            CustomSuccessOperation::GetAll { entries } => entries
+                .iter()
+                .map(|keyed| format_entry(&keyed.key, &keyed.entry))
+                .collect::<String>()
+                .strip_suffix('\n')
+                .unwrap_or_default()
+                .to_owned(),
+            CustomSuccessOperation::GetMerged { entries } => entries
+                .iter()
+                .map(|layered| {
+                    format_layered_entry(&layered.keyed.key, &layered.keyed.entry, layered.source)
+                })
+                .collect::<String>()
+                .strip_suffix('\n')
+                .unwrap_or_default()
+                .to_owned(),
+            CustomSuccessOperation::Find { matches } => matches
+                .iter()
+                .map(|found| found.format(false))
+                .collect::<String>()
+                .strip_suffix('\n')
+                .unwrap_or_default()
+                .to_owned(),
+            CustomSuccessOperation::Clear { count } => {
+                SuccessfulOperationStrings::successful_clear_operation(*count)
+            }
+            CustomSuccessOperation::Ttl {
+                key,
+                expires_at: Some(expires_at),
+                remaining,
+            } => SuccessfulOperationStrings::entry_expires_in(
+                key,
+                &time::format_duration(remaining.unwrap_or_default()),
+                &time::format_datetime(*expires_at),
+            ),
+            CustomSuccessOperation::Ttl { key, .. } => {
+                SuccessfulOperationStrings::entry_never_expires(key)
+            }
+            CustomSuccessOperation::History {
+                key,
+                current,
+                versions,
+            } => SuccessfulOperationStrings::entry_history(key, current.as_ref(), versions),
+            CustomSuccessOperation::Revert { key, entry, .. } => {
+                SuccessfulOperationStrings::successful_revert_operation(key, &entry.value)
+            }
+            CustomSuccessOperation::Undo { restored } => {
+                SuccessfulOperationStrings::successful_undo_operation(*restored)
+            }
+            CustomSuccessOperation::Namespaces { namespaces } => {
+                SuccessfulOperationStrings::namespaces(namespaces)
+            }
+            CustomSuccessOperation::CreateNamespace { namespace } => {
+                SuccessfulOperationStrings::successful_create_namespace_operation(namespace)
+            }
+            CustomSuccessOperation::RenameNamespace {
+                namespace,
+                new_namespace,
+                moved,
+            } => SuccessfulOperationStrings::successful_rename_namespace_operation(
+                namespace,
+                new_namespace,
+                *moved,
+            ),
+            CustomSuccessOperation::DropNamespace { namespace, deleted } => {
+                SuccessfulOperationStrings::successful_drop_namespace_operation(namespace, *deleted)
+            }
+            CustomSuccessOperation::RestoreBackup { backup_path } => {
+                SuccessfulOperationStrings::successful_restore_backup_operation(backup_path)
+            }
+            CustomSuccessOperation::Export { contents, .. } => {
+                contents.strip_suffix('\n').unwrap_or(contents).to_owned()
+            }
+            CustomSuccessOperation::Import {
+                changes,
+                dry_run: true,
+                ..
+            } => SuccessfulOperationStrings::import_preview(changes),
+            CustomSuccessOperation::Import {
+                source, changes, ..
+            } => {
+                let count = |kind: ImportChangeKind| {
+                    changes
+                        .iter()
+                        .filter(|change| change.change == kind)
+                        .count()
+                };
+                let overwritten = count(ImportChangeKind::Overwritten);
+                SuccessfulOperationStrings::successful_import_operation(
+                    source,
+                    count(ImportChangeKind::Added) + overwritten,
+                    overwritten,
+                    count(ImportChangeKind::Skipped),
+                )
+            }
+            CustomSuccessOperation::Run { command, exit_code } => {
+                SuccessfulOperationStrings::command_exited(command, *exit_code)
+            }
+            CustomSuccessOperation::Env { shell, variables } => {
+                let statements = environment::export_statements(*shell, variables);
+                statements
+                    .strip_suffix('\n')
+                    .unwrap_or(&statements)
+                    .to_owned()
+            }
+            CustomSuccessOperation::Render { contents, .. } => contents.clone(),
+            CustomSuccessOperation::Config { settings } => {
+                SuccessfulOperationStrings::configured_settings(settings)
+            }
+            CustomSuccessOperation::ConfigGet { setting } => setting.value.clone(),
+            CustomSuccessOperation::ConfigSet {
+                name, value, path, ..
+            } => SuccessfulOperationStrings::successful_config_set_operation(name, value, path),
+            CustomSuccessOperation::Init { path, source } => {
+                SuccessfulOperationStrings::successful_init_operation(path, &source.to_string())
+            }
+            CustomSuccessOperation::Which { path, .. } => path.clone(),
+            CustomSuccessOperation::Encrypt { path } => {
+                SuccessfulOperationStrings::successful_encrypt_operation(path)
+            }
+            CustomSuccessOperation::Decrypt { path } => {
+                SuccessfulOperationStrings::successful_decrypt_operation(path)
+            }
+            CustomSuccessOperation::Rekey { path } => {
+                SuccessfulOperationStrings::successful_rekey_operation(path)
+            }
+            CustomSuccessOperation::Repair {
+                quarantined,
+                quarantine_path: Some(quarantine_path),
+            } => SuccessfulOperationStrings::successful_repair_operation(
+                *quarantined,
+                quarantine_path,
+            ),
+            CustomSuccessOperation::Repair { .. } => {
+                SuccessfulOperationStrings::nothing_to_repair()
+            }
+        }
+    }
+
+    /// The data of this operation as table rows, with one row per entry or version. Missing
+    /// fields are left empty.
+    pub fn rows(&self) -> Vec<Vec<String>> {
+        match self {
+            CustomSuccessOperation::Insert { key, value } => vec![vec![key.clone(), value.clone()]],
+            CustomSuccessOperation::Get { key, entry, .. } => vec![entry_row(key, entry)],
+            CustomSuccessOperation::Explain {
+                key, entry, layer, ..
+            } => {
+                let mut row = entry_row(key, entry);
+                row.extend([layer.path.clone(), layer.source.to_string()]);
+                vec![row]
+            }
+            CustomSuccessOperation::Put {
+                key,
+                value,
+                old_value,
+            } => vec![vec![key.clone(), value.clone(), old_value.clone()]],
+            CustomSuccessOperation::Delete { key, old_value } => {
+                vec![vec![key.clone(), old_value.clone()]]
+            }
+            CustomSuccessOperation::GetAll { entries } => entries
+                .iter()
+                .map(|keyed| entry_row(&keyed.key, &keyed.entry))
+                .collect(),
+            CustomSuccessOperation::GetMerged { entries } => entries
+                .iter()
+                .map(|layered| {
+                    let mut row = entry_row(&layered.keyed.key, &layered.keyed.entry);
+                    row.push(layered.source.to_string());
+                    row
+                })
+                .collect(),
+            CustomSuccessOperation::Find { matches } => matches
+                .iter()
+                .map(|found| {
+                    vec![
+                        found.key.clone(),
+                        found.value.clone(),
+                        found.description.clone().unwrap_or_default(),
+                    ]
+                })
+                .collect(),
+            CustomSuccessOperation::Clear { count } => vec![vec![count.to_string()]],
+            CustomSuccessOperation::Ttl {
+                key,
+                expires_at,
+                remaining,
+            } => vec![vec![
+                key.clone(),
+                optional_cell(*expires_at),
+                optional_cell(*remaining),
+            ]],
+            CustomSuccessOperation::History { versions, .. } => versions
+                .iter()
+                .enumerate()
+                .map(|(index, version)| {
+                    let mut row = vec![(index + 1).to_string()];
+                    row.extend(entry_row("", &version.entry).into_iter().skip(1));
+                    row.push(version.replaced_at.to_string());
+                    row
+                })
+                .collect(),
+            CustomSuccessOperation::Revert {
+                key,
+                version,
+                entry,
+            } => {
+                let mut row = entry_row(key, entry);
+                row.push(version.to_string());
+                vec![row]
+            }
+            CustomSuccessOperation::Undo { restored } => vec![vec![restored.to_string()]],
+            CustomSuccessOperation::Namespaces { namespaces } => namespaces
+                .iter()
+                .map(|namespace| vec![namespace.name.clone(), namespace.count.to_string()])
+                .collect(),
+            CustomSuccessOperation::CreateNamespace { namespace } => vec![vec![namespace.clone()]],
+            CustomSuccessOperation::RenameNamespace {
+                namespace,
+                new_namespace,
+                moved,
+            } => vec![vec![
+                namespace.clone(),
+                new_namespace.clone(),
+                moved.to_string(),
+            ]],
+            CustomSuccessOperation::DropNamespace { namespace, deleted } => {
+                vec![vec![namespace.clone(), deleted.to_string()]]
+            }
+            CustomSuccessOperation::RestoreBackup { backup_path } => {
+                vec![vec![backup_path.clone()]]
+            }
+            CustomSuccessOperation::Export {
+                format,
+                count,
+                contents,
+            } => vec![vec![
+                format.to_string(),
+                count.to_string(),
+                contents.clone(),
+            ]],
+            CustomSuccessOperation::Import { changes, .. } => changes
+                .iter()
+                .map(|change| {
+                    vec![
+                        change.key.clone(),
+                        change.value.clone(),
+                        change.change.to_string(),
+                    ]
+                })
+                .collect(),
+            CustomSuccessOperation::Run { command, exit_code } => {
+                let mut row = vec![exit_code.to_string()];
+                row.extend(command.iter().cloned());
+                vec![row]
+            }
+            CustomSuccessOperation::Env { variables, .. } => variables
+                .iter()
+                .map(|variable| {
+                    vec![
+                        variable.name.clone(),
+                        variable.key.clone(),
+                        variable.value.clone(),
+                    ]
+                })
+                .collect(),
+            CustomSuccessOperation::Render {
+                source,
+                contents,
+                missing,
+            } => vec![vec![source.clone(), contents.clone(), missing.join(",")]],
+            CustomSuccessOperation::Config { settings } => {
+                settings.iter().map(setting_row).collect()
+            }
+            CustomSuccessOperation::ConfigGet { setting } => vec![setting_row(setting)],
+            CustomSuccessOperation::ConfigSet {
+                name,
+                value,
+                path,
+                scope,
+            } => vec![vec![
+                name.clone(),
+                value.clone(),
+                path.clone(),
+                scope.to_string(),
+            ]],
+            CustomSuccessOperation::Init { path, source }
+            | CustomSuccessOperation::Which { path, source } => {
+                vec![vec![path.clone(), source.to_string()]]
+            }
+            CustomSuccessOperation::Encrypt { path }
+            | CustomSuccessOperation::Decrypt { path }
+            | CustomSuccessOperation::Rekey { path } => vec![vec![path.clone()]],
+            CustomSuccessOperation::Repair {
+                quarantined,
+                quarantine_path,
+            } => vec![vec![
+                quarantined.to_string(),
+                quarantine_path.clone().unwrap_or_default(),
+            ]],
+        }
+    }
+}
+
+fn entry_row(key: &str, entry: &ReisbaseEntry) -> Vec<String> {
+    vec![
+        key.to_owned(),
+        entry.value.clone(),
+        entry.description.clone().unwrap_or_default(),
+        optional_cell(entry.expires_at),
+        entry.secret.to_string(),
+    ]
+}
+
+fn setting_row(setting: &ConfiguredSetting) -> Vec<String> {
+    vec![
+        setting.name.clone(),
+        setting.value.clone(),
+        setting.scope.to_string(),
+    ]
+}
+
+fn optional_cell(value: Option<u64>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+/// Formats an entry like [`format_entry`], followed by the layer it was read from.
+fn format_layered_entry(key: &str, entry: &ReisbaseEntry, source: LocationSource) -> String {
+    format!(
+        "{}{}({})\n",
+        format_entry(key, entry).trim_end_matches('\n'),
+        DatabaseStringConstants::ENTRIES_SEPARATOR,
+        source
+    )
+}
+
+fn format_entry(key: &str, entry: &ReisbaseEntry) -> String {
+    let description = entry
+        .description
+        .as_ref()
+        .map(|description| {
+            format!(
+                "{}{}{}",
+                DatabaseStringConstants::ENTRIES_SEPARATOR,
+                DatabaseStringConstants::DESCRIPTION_IDENTIFIER,
+                description
+            )
+        })
+        .unwrap_or_default();
+    let expiration = entry
+        .expires_at
+        .map(|expires_at| {
+            format!(
+                "{}{}{}",
+                DatabaseStringConstants::ENTRIES_SEPARATOR,
+                DatabaseStringConstants::EXPIRATION_IDENTIFIER,
+                time::format_datetime(expires_at)
+            )
+        })
+        .unwrap_or_default();
+
+    format!(
+        "{}{}{}{}{}{}\n",
+        DatabaseStringConstants::KEY_IDENTIFIER,
+        key,
+        DatabaseStringConstants::ENTRIES_SEPARATOR,
+        entry.value,
+        description,
+        expiration
+    )
 }
@@ -0,0 +1,93 @@
+//! "Did you mean" suggestions for keys and actions that don't exist.
+
+/// How many suggestions are offered at most.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Returns the candidates close enough to `target` to be a likely typo of it, from the
+/// closest to the farthest. Case is ignored when comparing. A candidate is never suggested
+/// if reaching it takes as many edits as `target` has characters, so a single character only
+/// matches itself in another case.
+pub fn closest<'a, I>(target: &str, candidates: I) -> Vec<&'a str>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let target = target.to_lowercase();
+    let length = target.chars().count();
+    let max_distance = (length / 3).max(1).min(length.saturating_sub(1));
+
+    let mut suggestions = candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(&target, &candidate.to_lowercase()), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect::<Vec<(usize, &str)>>();
+    suggestions.sort();
+    suggestions.dedup_by_key(|(_, candidate)| *candidate);
+    suggestions
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+/// Returns the suggestion closer to `target` than every other one, if there is one.
+/// `suggestions` must be sorted from the closest, as returned by [`closest`].
+pub fn unambiguous<'a, S: AsRef<str>>(target: &str, suggestions: &'a [S]) -> Option<&'a str> {
+    let target = target.to_lowercase();
+    let distance = |suggestion: &S| edit_distance(&target, &suggestion.as_ref().to_lowercase());
+    match suggestions {
+        [only] => Some(only.as_ref()),
+        [first, second, ..] if distance(first) < distance(second) => Some(first.as_ref()),
+        _ => None,
+    }
+}
+
+/// Optimal string alignment distance between `a` and `b`, counted in characters: the
+/// Levenshtein distance, except that swapping two adjacent characters counts as a single
+/// edit, since it is the most common typo.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<char>>();
+    let b = b.chars().collect::<Vec<char>>();
+    let mut rows = vec![(0..=b.len()).collect::<Vec<usize>>()];
+
+    for i in 1..=a.len() {
+        let mut row = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let previous_row = &rows[i - 1];
+            let substitution = previous_row[j - 1] + usize::from(a[i - 1] != b[j - 1]);
+            row[j] = substitution.min(previous_row[j] + 1).min(row[j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(rows[i - 2][j - 2] + 1);
+            }
+        }
+        rows.push(row);
+    }
+    rows[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn swapped_characters_are_a_single_edit() {
+        assert_eq!(edit_distance("gte", "get"), 1);
+        assert_eq!(edit_distance("ab", "ba"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn transposed_actions_are_suggested() {
+        assert_eq!(closest("gte", ["get", "set", "del"]), vec!["get"]);
+        assert_eq!(closest("improt", ["import", "export"]), vec!["import"]);
+    }
+
+    #[test]
+    fn short_keys_only_match_close_keys() {
+        assert!(closest("b", ["a", "c", "ab"]).is_empty());
+        assert_eq!(closest("b", ["B"]), vec!["B"]);
+        assert!(closest("ab", ["cd", "x"]).is_empty());
+        assert_eq!(closest("ab", ["ba", "cd"]), vec!["ba"]);
+        assert!(closest("", ["a"]).is_empty());
+    }
+}
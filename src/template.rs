@@ -0,0 +1,307 @@
+//! Templates that have the values of entries substituted into them, for the render action.
+//!
+//! A placeholder is written as `{{ key }}` or `${reis:key}`, and may be followed by the
+//! filters its value goes through, from left to right, each after a `|`: `{{ key | base64 }}`
+//! or `${reis:key|url-encode}`. Text that isn't shaped like a placeholder is left as it is.
+
+use base64::prelude::{Engine, BASE64_STANDARD};
+use regex::{Captures, Regex};
+
+/// Matches either kind of placeholder, capturing its key and the filters after it.
+const PLACEHOLDER_PATTERN: &str = r"\{\{\s*(?P<key>[^\s{}|]+)\s*(?P<filters>(?:\|\s*[^\s{}|]*\s*)*)\}\}|\$\{reis:(?P<reis_key>[^\s{}|]+)(?P<reis_filters>(?:\|[^\s{}|]*)*)\}";
+
+/// What rendering does with the placeholders whose keys don't exist.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MissingKeyPolicy {
+    /// Renders nothing, reporting every missing key.
+    #[default]
+    Fail,
+    /// Replaces them with the value passed with `--default`, or with nothing.
+    Default,
+    /// Keeps the placeholders as they are written.
+    Leave,
+}
+
+impl MissingKeyPolicy {
+    pub fn new(policy: &str) -> Option<MissingKeyPolicy> {
+        match policy {
+            "fail" => Some(MissingKeyPolicy::Fail),
+            "default" => Some(MissingKeyPolicy::Default),
+            "leave" => Some(MissingKeyPolicy::Leave),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for MissingKeyPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MissingKeyPolicy::Fail => write!(f, "fail"),
+            MissingKeyPolicy::Default => write!(f, "default"),
+            MissingKeyPolicy::Leave => write!(f, "leave"),
+        }
+    }
+}
+
+/// Changes the value of a placeholder before it is substituted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Filter {
+    Base64,
+    /// Percent-encodes every byte except the unreserved characters of URLs.
+    UrlEncode,
+    /// Escapes the value to be written inside a JSON string, without adding the quotes.
+    JsonEscape,
+}
+
+impl Filter {
+    pub fn new(filter: &str) -> Option<Filter> {
+        match filter {
+            "base64" => Some(Filter::Base64),
+            "url-encode" => Some(Filter::UrlEncode),
+            "json-escape" => Some(Filter::JsonEscape),
+            _ => None,
+        }
+    }
+
+    fn apply(&self, value: &str) -> String {
+        match self {
+            Filter::Base64 => BASE64_STANDARD.encode(value),
+            Filter::UrlEncode => value
+                .bytes()
+                .map(|byte| match byte {
+                    b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                        char::from(byte).to_string()
+                    }
+                    byte => format!("%{:02X}", byte),
+                })
+                .collect(),
+            Filter::JsonEscape => {
+                let quoted = serde_json::to_string(value).expect("strings are always valid JSON");
+                quoted[1..quoted.len() - 1].to_owned()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Placeholder {
+    /// The placeholder as it is written in the template.
+    text: String,
+    key: String,
+    filters: Vec<Filter>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Piece {
+    Text(String),
+    Placeholder(Placeholder),
+}
+
+/// A template split into its text and its placeholders.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    pieces: Vec<Piece>,
+}
+
+impl Template {
+    /// Splits `contents` into text and placeholders, failing if a placeholder has an unknown
+    /// filter.
+    pub fn parse(contents: &str) -> Result<Template, String> {
+        let pattern = Regex::new(PLACEHOLDER_PATTERN).expect("the placeholder pattern is valid");
+        let mut pieces = Vec::new();
+        let mut end = 0;
+        for captures in pattern.captures_iter(contents) {
+            let Some(matched) = captures.get(0) else {
+                continue;
+            };
+            pieces.push(Piece::Text(contents[end..matched.start()].to_owned()));
+            pieces.push(Piece::Placeholder(placeholder(&captures)?));
+            end = matched.end();
+        }
+        pieces.push(Piece::Text(contents[end..].to_owned()));
+        Ok(Template { pieces })
+    }
+
+    /// The keys of every placeholder, each once, in the order they first appear.
+    pub fn keys(&self) -> Vec<&str> {
+        let mut keys = Vec::new();
+        for piece in &self.pieces {
+            if let Piece::Placeholder(placeholder) = piece {
+                if !keys.contains(&placeholder.key.as_str()) {
+                    keys.push(placeholder.key.as_str());
+                }
+            }
+        }
+        keys
+    }
+
+    /// The keys `value_of` has no value for, each once, in the order they first appear. These
+    /// are the keys that fail rendering with [`MissingKeyPolicy::Fail`].
+    pub fn missing_keys<F>(&self, value_of: F) -> Vec<&str>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        self.keys()
+            .into_iter()
+            .filter(|key| value_of(key).is_none())
+            .collect()
+    }
+
+    /// Substitutes every placeholder with the value `value_of` returns for its key, or with
+    /// `missing` if it returns none. If neither has a value, the placeholder is kept.
+    pub fn render<F>(&self, value_of: F, missing: Option<&str>) -> String
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        self.pieces
+            .iter()
+            .map(|piece| match piece {
+                Piece::Text(text) => text.clone(),
+                Piece::Placeholder(placeholder) => {
+                    match value_of(&placeholder.key).or_else(|| missing.map(String::from)) {
+                        Some(value) => placeholder
+                            .filters
+                            .iter()
+                            .fold(value, |value, filter| filter.apply(&value)),
+                        None => placeholder.text.clone(),
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+fn placeholder(captures: &Captures) -> Result<Placeholder, String> {
+    let key = captures
+        .name("key")
+        .or_else(|| captures.name("reis_key"))
+        .map_or("", |key| key.as_str());
+    let filters = captures
+        .name("filters")
+        .or_else(|| captures.name("reis_filters"))
+        .map_or("", |filters| filters.as_str());
+    let filters = filters
+        .split('|')
+        .skip(1)
+        .map(str::trim)
+        .map(|filter| {
+            Filter::new(filter).ok_or_else(|| format!("the filter {} doesn't exist", filter))
+        })
+        .collect::<Result<Vec<Filter>, String>>()?;
+    Ok(Placeholder {
+        text: captures[0].to_owned(),
+        key: key.to_owned(),
+        filters,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(contents: &str, missing: Option<&str>) -> String {
+        let template = Template::parse(contents).expect("the template is valid");
+        template.render(
+            |key| match key {
+                "host" => Some(String::from("localhost")),
+                "db:url" => Some(String::from("a b/c?d=é&\"x\"")),
+                _ => None,
+            },
+            missing,
+        )
+    }
+
+    #[test]
+    fn both_placeholder_syntaxes_are_substituted() {
+        assert_eq!(render("{{host}}", None), "localhost");
+        assert_eq!(render("h={{  host  }};", None), "h=localhost;");
+        assert_eq!(render("${reis:host}", None), "localhost");
+        assert_eq!(render("{{ db:url }}", None), "a b/c?d=é&\"x\"");
+        assert_eq!(
+            render("{{ host }} and ${reis:host}", None),
+            "localhost and localhost"
+        );
+    }
+
+    #[test]
+    fn text_not_shaped_like_a_placeholder_is_kept() {
+        for text in [
+            "{ host }",
+            "{{ }}",
+            "${host}",
+            "${reis:}",
+            "{{ two keys }}",
+            "$HOME",
+        ] {
+            assert_eq!(render(text, None), text);
+        }
+    }
+
+    #[test]
+    fn filters_are_applied_from_left_to_right() {
+        assert_eq!(render("{{ host | base64 }}", None), "bG9jYWxob3N0");
+        assert_eq!(
+            render("${reis:db:url|url-encode}", None),
+            "a%20b%2Fc%3Fd%3D%C3%A9%26%22x%22"
+        );
+        assert_eq!(
+            render("{{ db:url | json-escape }}", None),
+            r#"a b/c?d=é&\"x\""#
+        );
+        assert_eq!(
+            render("{{ host | base64 | url-encode }}", None),
+            "bG9jYWxob3N0"
+        );
+        assert_eq!(
+            render("{{ db:url | json-escape | base64 }}", None),
+            BASE64_STANDARD.encode(r#"a b/c?d=é&\"x\""#)
+        );
+    }
+
+    #[test]
+    fn unknown_filters_are_refused() {
+        assert_eq!(
+            Template::parse("{{ host | rot13 }}"),
+            Err(String::from("the filter rot13 doesn't exist"))
+        );
+        assert!(Template::parse("${reis:host|}").is_err());
+    }
+
+    #[test]
+    fn keys_are_listed_once_in_order() {
+        let template = Template::parse("{{ b }} ${reis:a} {{ b | base64 }} {{ c }}")
+            .expect("the template is valid");
+        assert_eq!(template.keys(), vec!["b", "a", "c"]);
+    }
+
+    #[test]
+    fn missing_keys_follow_the_policy() {
+        // Failing reports every missing key, so nothing is rendered.
+        let template = Template::parse("{{ port }} {{ host }} ${reis:user} {{ port }}")
+            .expect("the template is valid");
+        let value_of = |key: &str| (key == "host").then(|| String::from("localhost"));
+        assert_eq!(template.missing_keys(value_of), vec!["port", "user"]);
+
+        assert_eq!(MissingKeyPolicy::new("fail"), Some(MissingKeyPolicy::Fail));
+        assert_eq!(
+            MissingKeyPolicy::new("default"),
+            Some(MissingKeyPolicy::Default)
+        );
+        assert_eq!(
+            MissingKeyPolicy::new("leave"),
+            Some(MissingKeyPolicy::Leave)
+        );
+        assert_eq!(MissingKeyPolicy::new("ignore"), None);
+        assert_eq!(MissingKeyPolicy::default(), MissingKeyPolicy::Fail);
+
+        // The default policy renders the default value, which goes through the filters.
+        assert_eq!(render("{{ port }}:{{ host }}", Some("80")), "80:localhost");
+        assert_eq!(render("{{ port | base64 }}", Some("80")), "ODA=");
+        assert_eq!(render("[{{ port }}]", Some("")), "[]");
+        // Leaving them keeps the placeholders as they are written.
+        assert_eq!(
+            render("{{ port|base64 }} ${reis:port}", None),
+            "{{ port|base64 }} ${reis:port}"
+        );
+    }
+}
@@ -1,95 +1,361 @@
 use std::env;
-use std::io;
-use strum::IntoEnumIterator;
+use std::io::{self, IsTerminal};
+use std::process::ExitCode;
 
 use crate::actions::ReisbaseAction;
+use crate::arguments::ReisbaseActionsArguments;
+use crate::clipboard;
 use crate::constants::{
-    the_entry_does_not_exists, the_key_already_exists, CANCELED_OPERATION, EMPTY_DATABASE,
-    THIS_ACTION_IS_PERMANENT,
+    did_you_mean, did_you_mean_the_action, do_you_want_to_run, ClipboardConstants, ExitCodes,
+    ARE_YOU_SURE, CANCELED_OPERATION, CONFIRMATION_CHOICES, CONFIRMATION_REQUIRED,
+    DO_YOU_WANT_TO_REPLACE_IT, DO_YOU_WANT_TO_RUN_IT, THIS_ACTION_IS_PERMANENT,
 };
+use crate::entry::SensitiveValue;
 use crate::operation::Operation;
+use crate::output::{self, OutputFormat};
 use crate::{
     failures::{CustomFailureOperation, CustomReisActionWarning, CustomReisIOFailure},
+    global_options::{ColorChoice, Confirmation, ReisbaseGlobalOptions},
     interface::Interface,
     success::CustomSuccessOperation,
+    suggestions,
 };
 
 #[derive(Debug)]
 pub struct TerminalCommunication;
 
 impl TerminalCommunication {
-    pub fn execute() {
-        let operation = get_requested_operation();
-        handle_interface_execution(operation);
+    /// Executes the operation requested through the command line arguments, returning the
+    /// status reisbase should exit with, as documented in [`ExitCodes`].
+    pub fn execute() -> ExitCode {
+        let exit_code = match ReisbaseGlobalOptions::parse(env::args().skip(1).collect()) {
+            Ok((options, args)) if is_restore_helper(&args) => handle_clipboard_restore(&options),
+            Ok((options, args)) => handle_requested_operation(args, &options),
+            Err(error) => handle_error_operation(&error, OutputFormat::default()),
+        };
+        ExitCode::from(exit_code)
     }
 }
 
-fn handle_interface_execution(operation: Option<Operation>) {
-    let result = Interface::execute(operation);
-    match result {
-        Ok(operation) => handle_success_operation(&operation),
-        Err(operation) => handle_failure_operation(&operation),
+fn is_restore_helper(args: &[String]) -> bool {
+    args.first()
+        .is_some_and(|action| action == ClipboardConstants::RESTORE_HELPER_ACTION)
+}
+
+/// Runs as the detached helper that restores the clipboard after a value was copied to it.
+/// Nobody is left to read its output, so it is only reported through the exit status.
+fn handle_clipboard_restore(options: &ReisbaseGlobalOptions) -> u8 {
+    match clipboard::restore_after_delay(options.clipboard) {
+        Ok(_) => ExitCodes::SUCCESS,
+        Err(_) => ExitCodes::IO_FAILURE,
+    }
+}
+
+/// Executes the operation described by `args`. If its action doesn't exist but is a likely
+/// typo of one that does, offers to run that one instead when the user can be asked.
+fn handle_requested_operation(args: Vec<String>, options: &ReisbaseGlobalOptions) -> u8 {
+    let mut args = match expand_config_verb(expand_alias(args, options)) {
+        Ok(args) => args,
+        Err(error) => return handle_error_operation(&error, options.output),
     };
+    let suggestion = args
+        .first()
+        .filter(|_| options.output == OutputFormat::Plain && can_ask(options))
+        .filter(|action| ReisbaseAction::first(action).is_none())
+        .and_then(|action| {
+            suggestions::unambiguous(action, &ReisbaseAction::suggestions(action)).map(String::from)
+        });
+
+    match suggestion {
+        Some(suggestion) => {
+            let action = std::mem::replace(&mut args[0], suggestion.clone());
+            retry(
+                &did_you_mean_the_action(&action, &suggestion),
+                DO_YOU_WANT_TO_RUN_IT,
+                ExitCodes::UNKNOWN_ACTION,
+                options,
+                || handle_interface_execution(get_requested_operation(args), options),
+            )
+        }
+        None => handle_interface_execution(get_requested_operation(args), options),
+    }
+}
+
+/// Replaces a configured alias used as the action with the action and arguments it is set
+/// to. The names of existing actions are never replaced.
+fn expand_alias(mut args: Vec<String>, options: &ReisbaseGlobalOptions) -> Vec<String> {
+    let expansion = args
+        .first()
+        .filter(|action| ReisbaseAction::first(action).is_none())
+        .and_then(|action| options.aliases.get(action));
+    if let Some(expansion) = expansion {
+        let expanded = expansion.split_whitespace().map(String::from);
+        args.splice(..1, expanded.collect::<Vec<String>>());
+    }
+    args
+}
+
+/// Replaces the `list`, `get` and `set` subcommands of the config action with the actions
+/// they stand for, so `config get output` runs `config-get output`. Any other word after the
+/// config action is refused rather than ignored.
+fn expand_config_verb(mut args: Vec<String>) -> Result<Vec<String>, CustomReisIOFailure> {
+    let is_config = args
+        .first()
+        .and_then(|action| ReisbaseAction::first(action))
+        .is_some_and(|action| matches!(action, ReisbaseAction::Config { .. }));
+    let verb = args
+        .get(1)
+        .filter(|_| is_config)
+        .filter(|verb| !ReisbaseActionsArguments::is_argument(verb))
+        .cloned();
+    match verb.as_deref() {
+        None => {}
+        Some("list") => {
+            args.remove(1);
+        }
+        Some("get") => {
+            args.splice(..2, [String::from("config-get")]);
+        }
+        Some("set") => {
+            args.splice(..2, [String::from("config-set")]);
+        }
+        Some(verb) => return Err(CustomReisIOFailure::unknown_config_verb(verb)),
+    }
+    Ok(args)
+}
+
+fn handle_interface_execution(operation: Option<Operation>, options: &ReisbaseGlobalOptions) -> u8 {
+    let result = Interface::execute(operation, options);
+    match result {
+        Ok(operation) => {
+            handle_success_operation(&operation, options);
+            operation.exit_code()
+        }
+        Err(operation) => handle_failure_operation(&operation, options),
+    }
 }
 
-fn handle_success_operation(success: &CustomSuccessOperation) {
-    println!("{}", success.message());
+fn handle_success_operation(success: &CustomSuccessOperation, options: &ReisbaseGlobalOptions) {
+    match (options.output, success) {
+        (OutputFormat::Json, _) => println!("{}", output::success_to_json(success)),
+        (OutputFormat::Tsv, _) => println!("{}", output::success_to_tsv(success)),
+        // The command already printed everything there is to read.
+        (OutputFormat::Plain, CustomSuccessOperation::Run { .. }) => {}
+        (OutputFormat::Plain, CustomSuccessOperation::Find { matches }) => {
+            let highlighted = should_highlight(options.color);
+            for found in matches {
+                print!("{}", found.format(highlighted));
+            }
+        }
+        (OutputFormat::Plain, _) => println!("{}", success.message()),
+    }
 }
 
-fn handle_failure_operation(failure: &CustomFailureOperation) {
+/// Unless colors are always or never used, highlighting is only used when printing to a
+/// terminal, and never when `NO_COLOR` is set.
+fn should_highlight(color: ColorChoice) -> bool {
+    match color {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none(),
+    }
+}
+
+/// Reports a failed operation, returning the status to exit with. Warnings may be retried
+/// after a confirmation, in which case the status is the one of the retried operation.
+fn handle_failure_operation(
+    failure: &CustomFailureOperation,
+    options: &ReisbaseGlobalOptions,
+) -> u8 {
     match failure {
-        CustomFailureOperation::Error(error) => handle_error_operation(error),
-        CustomFailureOperation::Warning(warning) => handle_warning_operation(warning),
+        CustomFailureOperation::Error(error) => handle_error_operation(error, options.output),
+        CustomFailureOperation::Warning(warning) => match options.output {
+            OutputFormat::Plain => handle_warning_operation(warning, options),
+            OutputFormat::Json => {
+                println!("{}", output::warning_to_json(warning));
+                failure.exit_code()
+            }
+            OutputFormat::Tsv => {
+                eprintln!("{}", output::warning_to_tsv(warning));
+                failure.exit_code()
+            }
+        },
     }
 }
 
-fn handle_error_operation(error: &CustomReisIOFailure) {
-    let error_message = error.error_message();
-    println!("{}", error_message);
-    error_message.print_error();
+fn handle_error_operation(error: &CustomReisIOFailure, output: OutputFormat) -> u8 {
+    match output {
+        OutputFormat::Plain => {
+            let error_message = error.error_message();
+            println!("{}", error_message);
+            error_message.print_error();
+        }
+        OutputFormat::Json => println!("{}", output::error_to_json(error)),
+        OutputFormat::Tsv => eprintln!("{}", output::error_to_tsv(error)),
+    }
+    error.exit_code()
 }
 
-fn handle_warning_operation(warning: &CustomReisActionWarning) {
+fn handle_warning_operation(
+    warning: &CustomReisActionWarning,
+    options: &ReisbaseGlobalOptions,
+) -> u8 {
+    let exit_code = warning.exit_code();
     match warning {
-        CustomReisActionWarning::EmptyDatabase => {
-            println!("{}", EMPTY_DATABASE)
-        }
         CustomReisActionWarning::EntryAlreadyExists {
-            key,
-            old_value,
             new_value,
+            key,
+            arguments,
+            ..
+        } => retry(
+            &warning.message(),
+            DO_YOU_WANT_TO_REPLACE_IT,
+            exit_code,
+            options,
+            || retry_put(key, new_value.reveal(), arguments, options),
+        ),
+        CustomReisActionWarning::EntryDoesntExists {
+            key,
+            value,
+            action,
+            arguments,
+            suggestions,
         } => {
-            retry(&the_key_already_exists(key, old_value), || {
-                retry_put(key, new_value);
-            });
+            println!("{}", warning.message());
+            // A suggested key is never used without asking, since the action would run on
+            // an entry the user didn't name.
+            match suggestions::unambiguous(key, suggestions).filter(|_| can_ask(options)) {
+                Some(suggestion) => retry(
+                    &did_you_mean(&[suggestion]),
+                    &do_you_want_to_run(action, suggestion),
+                    exit_code,
+                    options,
+                    || {
+                        let operation = Operation::with_key(
+                            action,
+                            suggestion,
+                            value.as_ref().map(SensitiveValue::reveal),
+                            arguments,
+                        );
+                        handle_interface_execution(Some(operation), options)
+                    },
+                ),
+                None => {
+                    if !suggestions.is_empty() {
+                        println!("{}", did_you_mean(suggestions));
+                    }
+                    exit_code
+                }
+            }
         }
-        CustomReisActionWarning::EntryDoesntExists { key, value } => {
-            let value = value.as_deref().unwrap_or("value");
-            println!("{}", the_entry_does_not_exists(key, value));
+        CustomReisActionWarning::ImportConflicts {
+            conflicts,
+            prompt: true,
+            ..
+        } => {
+            println!("{}", warning.message());
+            conflicts
+                .iter()
+                .map(|conflict| handle_warning_operation(conflict, options))
+                .fold(ExitCodes::SUCCESS, |status, conflict_status| {
+                    if status == ExitCodes::SUCCESS {
+                        conflict_status
+                    } else {
+                        status
+                    }
+                })
         }
-        CustomReisActionWarning::RequiredArgumentsNotSpecified { operation } => {
-            if let ReisbaseAction::Clear { arguments: _ } = operation {
-                retry(THIS_ACTION_IS_PERMANENT, retry_clear);
-            }
+        CustomReisActionWarning::RequiredArgumentsNotSpecified {
+            operation,
+            key,
+            arguments,
+        } if matches!(
+            **operation,
+            ReisbaseAction::Clear { .. } | ReisbaseAction::DropNamespace { .. }
+        ) =>
+        {
+            retry(
+                THIS_ACTION_IS_PERMANENT,
+                ARE_YOU_SURE,
+                exit_code,
+                options,
+                || retry_forced(operation, key.as_deref(), arguments, options),
+            )
+        }
+        _ => {
+            println!("{}", warning.message());
+            exit_code
         }
     }
 }
 
-fn retry<F: FnOnce()>(prompt: &str, retry_f: F) {
-    let input = get_user_input(prompt).map(|input| user_input_to_bool(&input));
+/// Asks the user to confirm retrying an operation after explaining why, with `message`.
+/// Returns the status of the retried operation, or `canceled_exit_code` if it wasn't
+/// retried. Without a terminal to ask on, the answer comes from the global options.
+fn retry<F: FnOnce() -> u8>(
+    message: &str,
+    question: &str,
+    canceled_exit_code: u8,
+    options: &ReisbaseGlobalOptions,
+    retry_f: F,
+) -> u8 {
+    match assumed_answer(options) {
+        Some(true) => return retry_f(),
+        Some(false) => {
+            println!("{} {}", message, CONFIRMATION_REQUIRED);
+            return canceled_exit_code;
+        }
+        None => {}
+    }
+
+    let prompt = format!("{} {} {}", message, question, CONFIRMATION_CHOICES);
+    let input = get_user_input(&prompt).map(|input| user_input_to_bool(&input));
     match input {
         Ok(true) => retry_f(),
-        Ok(false) => println!("{}", CANCELED_OPERATION),
-        Err(error) => handle_retry_error(&error),
-    };
+        Ok(false) => {
+            println!("{}", CANCELED_OPERATION);
+            canceled_exit_code
+        }
+        Err(error) => {
+            handle_retry_error(&error);
+            ExitCodes::IO_FAILURE
+        }
+    }
 }
 
-fn retry_clear() {
-    handle_interface_execution(Some(Operation::clear()));
+/// Returns the answer given to every question without asking, if the user can't or
+/// shouldn't be asked.
+fn assumed_answer(options: &ReisbaseGlobalOptions) -> Option<bool> {
+    match options.confirmation {
+        Confirmation::AssumeYes => Some(true),
+        Confirmation::AssumeNo => Some(false),
+        Confirmation::Ask if !io::stdin().is_terminal() => Some(false),
+        Confirmation::Ask => None,
+    }
+}
+
+/// Whether the user is asked to confirm, rather than having the answer assumed.
+fn can_ask(options: &ReisbaseGlobalOptions) -> bool {
+    assumed_answer(options).is_none()
 }
 
-fn retry_put(key: &str, value: &str) {
-    handle_interface_execution(Some(Operation::put(key, value)));
+fn retry_forced(
+    action: &ReisbaseAction,
+    key: Option<&str>,
+    arguments: &[ReisbaseActionsArguments],
+    options: &ReisbaseGlobalOptions,
+) -> u8 {
+    let operation = Operation::forced(action.full_name(), key, arguments);
+    handle_interface_execution(Some(operation), options)
+}
+
+fn retry_put(
+    key: &str,
+    value: &str,
+    arguments: &[ReisbaseActionsArguments],
+    options: &ReisbaseGlobalOptions,
+) -> u8 {
+    handle_interface_execution(Some(Operation::put(key, value, arguments)), options)
 }
 
 fn handle_retry_error(error: &io::Error) {
@@ -105,29 +371,29 @@ fn get_user_input(prompt: &str) -> Result<String, std::io::Error> {
 }
 
 fn user_input_to_bool(input: &str) -> bool {
-    input.to_lowercase().starts_with('y')
+    input.trim().to_lowercase().starts_with('y')
 }
 
-fn get_requested_operation() -> Option<Operation> {
-    let mut args = env::args().skip(1);
-    let action = args.next();
-    ReisbaseAction::iter()
-        .find(|reisbase_action| {
-            action
-                .as_ref()
-                .map(|ac| reisbase_action.has_same_name(ac))
-                .unwrap_or(false)
-        })
-        .and_then(|reisbase_action| parse_action(&reisbase_action, action.as_deref(), args))
+fn get_requested_operation(args: Vec<String>) -> Option<Operation> {
+    let mut args = args.into_iter();
+    let action = args.next()?;
+    match ReisbaseAction::first(&action) {
+        Some(reisbase_action) => parse_action(&reisbase_action, action, args),
+        // Unknown actions are still requested, so they are reported along with suggestions.
+        None => Operation::new(Some(action), None, None, args.collect()),
+    }
 }
 
 fn parse_action(
     reisbase_action: &ReisbaseAction,
-    action: Option<&str>,
-    mut args: impl Iterator<Item = String>,
+    action: String,
+    args: impl Iterator<Item = String>,
 ) -> Option<Operation> {
+    let mut args = args.peekable();
     let key = reisbase_action.with_key(|| args.next());
-    let value = reisbase_action.with_value(|| args.next());
+    // The value can be left out in favor of an argument telling where to read it from.
+    let value = reisbase_action
+        .with_value(|| args.next_if(|arg| !ReisbaseActionsArguments::is_argument(arg)));
     let args = args.collect::<Vec<String>>();
-    Operation::new(action.map(|s| s.to_owned()), key, value, args)
+    Operation::new(Some(action), key, value, args)
 }
@@ -0,0 +1,186 @@
+//! Timestamps used for entry expiration, as seconds since the Unix epoch in UTC.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_MINUTE: u64 = 60;
+const SECONDS_PER_HOUR: u64 = 60 * SECONDS_PER_MINUTE;
+const SECONDS_PER_DAY: u64 = 24 * SECONDS_PER_HOUR;
+const SECONDS_PER_WEEK: u64 = 7 * SECONDS_PER_DAY;
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+/// Parses durations such as `90`, `15m` or `1h30m`. A number without a unit is in seconds,
+/// and the supported units are `s`, `m`, `h`, `d` and `w`.
+pub fn parse_duration(duration: &str) -> Option<u64> {
+    if duration.is_empty() {
+        return None;
+    }
+
+    let mut total: u64 = 0;
+    let mut amount = String::new();
+    for character in duration.chars() {
+        if character.is_ascii_digit() {
+            amount.push(character);
+            continue;
+        }
+        let unit = match character {
+            's' => 1,
+            'm' => SECONDS_PER_MINUTE,
+            'h' => SECONDS_PER_HOUR,
+            'd' => SECONDS_PER_DAY,
+            'w' => SECONDS_PER_WEEK,
+            _ => return None,
+        };
+        let value = amount.parse::<u64>().ok()?;
+        total = total.checked_add(value.checked_mul(unit)?)?;
+        amount.clear();
+    }
+    if !amount.is_empty() {
+        total = total.checked_add(amount.parse::<u64>().ok()?)?;
+    }
+    Some(total)
+}
+
+/// Formats a duration as its largest units, such as `1d 2h` or `14m 59s`.
+pub fn format_duration(seconds: u64) -> String {
+    let units = [
+        (SECONDS_PER_DAY, "d"),
+        (SECONDS_PER_HOUR, "h"),
+        (SECONDS_PER_MINUTE, "m"),
+        (1, "s"),
+    ];
+    let mut remaining = seconds;
+    let parts = units
+        .iter()
+        .filter_map(|(unit_seconds, unit)| {
+            let amount = remaining / unit_seconds;
+            remaining %= unit_seconds;
+            (amount > 0).then(|| format!("{}{}", amount, unit))
+        })
+        .take(2)
+        .collect::<Vec<String>>();
+
+    if parts.is_empty() {
+        String::from("0s")
+    } else {
+        parts.join(" ")
+    }
+}
+
+/// Parses a UTC date as `YYYY-MM-DD`, `YYYY-MM-DDTHH:MM` or `YYYY-MM-DDTHH:MM:SS`, with an
+/// optional trailing `Z`.
+pub fn parse_datetime(datetime: &str) -> Option<u64> {
+    let datetime = datetime.strip_suffix('Z').unwrap_or(datetime);
+    let (date, time) = datetime
+        .split_once(['T', ' '])
+        .unwrap_or((datetime, "00:00"));
+
+    let mut date = date.split('-').map(|part| part.parse::<i64>().ok());
+    let (year, month, day) = (date.next()??, date.next()??, date.next()??);
+    if date.next().is_some() {
+        return None;
+    }
+
+    let mut time = time.split(':').map(|part| part.parse::<u64>().ok());
+    let (hour, minute) = (time.next()??, time.next()??);
+    let second = time.next().unwrap_or(Some(0))?;
+    if time.next().is_some() || hour > 23 || minute > 59 || second > 59 {
+        return None;
+    }
+
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let days = days_from_civil(year, month, day)?;
+    if civil_from_days(days) != (year, month, day) {
+        return None;
+    }
+    u64::try_from(days)
+        .ok()?
+        .checked_mul(SECONDS_PER_DAY)?
+        .checked_add(hour * SECONDS_PER_HOUR + minute * SECONDS_PER_MINUTE + second)
+}
+
+/// Formats a timestamp as `YYYY-MM-DDTHH:MM:SSZ`.
+pub fn format_datetime(timestamp: u64) -> String {
+    let (year, month, day) = civil_from_days((timestamp / SECONDS_PER_DAY) as i64);
+    let seconds = timestamp % SECONDS_PER_DAY;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        seconds / SECONDS_PER_HOUR,
+        seconds % SECONDS_PER_HOUR / SECONDS_PER_MINUTE,
+        seconds % SECONDS_PER_MINUTE
+    )
+}
+
+/// Days since the Unix epoch of a date in the proleptic Gregorian calendar, or [`None`] if
+/// the year is too far away to count them.
+fn days_from_civil(year: i64, month: i64, day: i64) -> Option<i64> {
+    let year = if month <= 2 {
+        year.checked_sub(1)?
+    } else {
+        year
+    };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era.checked_mul(146097)?
+        .checked_add(day_of_era)?
+        .checked_sub(719468)
+}
+
+/// Reverses [`days_from_civil`].
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dates_are_parsed_as_utc_timestamps() {
+        assert_eq!(parse_datetime("1970-01-01"), Some(0));
+        assert_eq!(parse_datetime("2000-02-29T12:30:15Z"), Some(951827415));
+        assert_eq!(parse_datetime("2001-02-29"), None);
+        assert_eq!(parse_datetime("1969-12-31T23:59"), None);
+    }
+
+    #[test]
+    fn dates_too_far_away_are_rejected_instead_of_overflowing() {
+        assert_eq!(parse_datetime("999999999999-01-01"), None);
+        assert_eq!(parse_datetime("9223372036854775807-12-31"), None);
+        assert_eq!(parse_datetime("-9223372036854775808-01-01"), None);
+        assert_eq!(parse_datetime("2000-9223372036854775807-01"), None);
+    }
+
+    #[test]
+    fn formatted_dates_are_parsed_back() {
+        for timestamp in [0, 951827415, 4102444800, u64::MAX / SECONDS_PER_DAY] {
+            assert_eq!(parse_datetime(&format_datetime(timestamp)), Some(timestamp));
+        }
+    }
+}